@@ -0,0 +1,38 @@
+#!/usr/bin/env sh
+# Copyright © 2025 Nantsa Montillet
+# SPDX-License-Identifier: AGPL-3.0-or-later
+
+# Converts an OPUS-MT model to the files read by the offline translator.
+# Usage: scripts/convert-opus-mt.sh <pair> <models folder>, e.g. scripts/convert-opus-mt.sh en-fr ~/models
+# Needs curl and rten-convert (pip install rten-convert).
+
+set -eu
+
+if [ $# -ne 2 ]; then
+    echo "Usage: $0 <pair> <models folder>" >&2
+    exit 1
+fi
+
+pair=$1
+out="$2/translation/opus-mt-$pair"
+repo="https://huggingface.co/Xenova/opus-mt-$pair/resolve/main"
+tmp=$(mktemp -d)
+trap 'rm -rf "$tmp"' EXIT
+
+mkdir -p "$out"
+for part in encoder decoder; do
+    curl -fL "$repo/onnx/${part}_model.onnx" -o "$tmp/${part}_model.onnx"
+    rten-convert "$tmp/${part}_model.onnx" "$out/${part}_model.rten"
+done
+curl -fL "$repo/tokenizer.json" -o "$out/tokenizer.json"
+curl -fL "$repo/config.json" -o "$out/config.json"
+
+# A mirror serves the checksums with the files, the app refuses files they do not list.
+files="encoder_model.rten decoder_model.rten tokenizer.json config.json"
+if command -v sha256sum >/dev/null; then
+    (cd "$out" && sha256sum $files > SHA256SUMS)
+else
+    (cd "$out" && shasum -a 256 $files > SHA256SUMS)
+fi
+
+echo "Converted opus-mt-$pair into $out"
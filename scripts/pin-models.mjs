@@ -0,0 +1,26 @@
+// Copyright © 2025 Nantsa Montillet
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+// Downloads every model of src-tauri/models.json and writes its sha256 and size back, the app
+// then verifies downloads against them. Point `url` at a versioned location first, so the
+// pinned file cannot change under it.
+// Usage: node scripts/pin-models.mjs
+
+import {createHash} from "node:crypto";
+import {readFile, writeFile} from "node:fs/promises";
+
+const manifestPath = new URL("../src-tauri/models.json", import.meta.url);
+const entries = JSON.parse(await readFile(manifestPath, "utf8"));
+
+for (const entry of entries) {
+    const response = await fetch(entry.url);
+    if (!response.ok) {
+        throw new Error(`${entry.url}: ${response.status} ${response.statusText}`);
+    }
+    const data = Buffer.from(await response.arrayBuffer());
+    entry.sha256 = createHash("sha256").update(data).digest("hex");
+    entry.size = data.length;
+    console.log(`${entry.id}: ${entry.sha256} (${entry.size} bytes)`);
+}
+
+await writeFile(manifestPath, JSON.stringify(entries, null, 4) + "\n");
@@ -0,0 +1,481 @@
+// Copyright © 2025 Nantsa Montillet
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::config::{CaptureRegion, Region, WindowTarget};
+use crate::errors::TranscendiaError;
+use image::imageops::{grayscale, resize, FilterType};
+use image::{Rgb, RgbImage, RgbaImage};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::{channel, Receiver, Sender};
+
+/// Side of the grayscale thumbnail compared between frames.
+const SIGNATURE_SIZE: usize = 128;
+/// Side of the tiles the thumbnails are compared by.
+const SIGNATURE_TILE: usize = 8;
+/// Region capture is tried again this long after it failed, the error may have been transient.
+const REGION_CAPTURE_RETRY: Duration = Duration::from_secs(30);
+/// Captures waiting to run, the capture stage asks for one and waits for its result.
+const CAPTURE_QUEUE_SIZE: usize = 1;
+
+type CaptureResult = Result<Capture, TranscendiaError>;
+
+#[derive(Clone)]
+pub struct Frame {
+    pub image: RgbImage,
+    pub scale_factor: f32,
+}
+
+/// Looks up a monitor by id, falling back to the first one when it was unplugged.
+pub fn find_monitor(id: u32) -> Result<xcap::Monitor, TranscendiaError> {
+    let monitors = xcap::Monitor::all().map_err(|_| TranscendiaError::CannotGetMonitors)?;
+    let position = monitors
+        .iter()
+        .position(|m| m.id().is_ok_and(|m_id| m_id == id))
+        .unwrap_or(0);
+    monitors
+        .into_iter()
+        .nth(position)
+        .ok_or(TranscendiaError::MonitorNotFound(id))
+}
+
+/// Finds the window a region follows: same app and title if possible, else any window of the app.
+pub fn find_window(target: &WindowTarget) -> Result<xcap::Window, TranscendiaError> {
+    let windows = xcap::Window::all()?;
+    let same_app = |w: &&xcap::Window| w.app_name().is_ok_and(|n| n == target.app_name);
+    windows
+        .iter()
+        .filter(same_app)
+        .find(|w| w.title().is_ok_and(|t| t == target.title))
+        .or_else(|| {
+            windows
+                .iter()
+                .filter(same_app)
+                .find(|w| !w.is_minimized().unwrap_or(false))
+        })
+        .cloned()
+        .ok_or_else(|| TranscendiaError::WindowNotFound(target.app_name.clone()))
+}
+
+/// Logical position of a monitor's top-left corner.
+pub fn monitor_origin(monitor: &xcap::Monitor) -> Result<(f32, f32), TranscendiaError> {
+    let scale = monitor.scale_factor()?;
+    Ok((monitor.x()? as f32 * scale, monitor.y()? as f32 * scale))
+}
+
+/// Logical position of a window's top-left corner.
+pub fn window_origin(window: &xcap::Window) -> Result<(f32, f32), TranscendiaError> {
+    let scale = window.current_monitor()?.scale_factor()?;
+    Ok((window.x()? as f32 * scale, window.y()? as f32 * scale))
+}
+
+/// Logical size of a window, in the units of `window_origin`.
+fn window_size(window: &xcap::Window) -> Result<(f32, f32), TranscendiaError> {
+    let scale = window.current_monitor()?.scale_factor()?;
+    Ok((
+        window.width()? as f32 * scale,
+        window.height()? as f32 * scale,
+    ))
+}
+
+/// Point the region of a capture region is relative to. A followed window that is not open
+/// falls back to its monitor, so the overlay still has somewhere to go until it shows up.
+pub fn region_origin(capture_region: &CaptureRegion) -> Result<(f32, f32), TranscendiaError> {
+    if let Some(window) = capture_region
+        .window
+        .as_ref()
+        .and_then(|target| find_window(target).ok())
+    {
+        return window_origin(&window);
+    }
+    monitor_origin(&find_monitor(capture_region.monitor)?)
+}
+
+enum CaptureSource {
+    Monitor {
+        monitor: xcap::Monitor,
+        /// Set when region capture failed, full captures are cropped until then.
+        region_capture_retry_at: Option<Instant>,
+    },
+    Window {
+        window: xcap::Window,
+        /// Origin of the window and region clamped to its size, as last given to the overlay.
+        last_placement: Option<((f32, f32), Region)>,
+    },
+}
+
+/// Grabs a region of a monitor or window every tick.
+pub struct RegionCapturer {
+    source: CaptureSource,
+    region: Region,
+    scale_factor: f32,
+    /// Last frame handed out, written over by the next capture once nobody else holds it.
+    recycled: Option<Arc<Frame>>,
+}
+
+impl RegionCapturer {
+    pub fn new(monitor: xcap::Monitor, region: Region) -> Result<Self, TranscendiaError> {
+        let scale_factor = monitor.scale_factor()?;
+        Ok(Self {
+            source: CaptureSource::Monitor {
+                monitor,
+                region_capture_retry_at: None,
+            },
+            region,
+            scale_factor,
+            recycled: None,
+        })
+    }
+
+    /// The region is relative to the window and moves with it. Resizing the window does not
+    /// scale the region, it keeps its place from the top-left corner and is clamped to the window.
+    pub fn for_window(window: xcap::Window, region: Region) -> Result<Self, TranscendiaError> {
+        let scale_factor = window.current_monitor()?.scale_factor()?;
+        Ok(Self {
+            source: CaptureSource::Window {
+                window,
+                last_placement: None,
+            },
+            region,
+            scale_factor,
+            recycled: None,
+        })
+    }
+
+    /// New origin and clamped region of a followed window when it moved or was resized since
+    /// the last call, including the first.
+    pub fn moved_placement(&mut self) -> Result<Option<((f32, f32), Region)>, TranscendiaError> {
+        let CaptureSource::Window {
+            window,
+            last_placement,
+        } = &mut self.source
+        else {
+            return Ok(None);
+        };
+
+        let placement = (
+            window_origin(window)?,
+            self.region.clamp_to(window_size(window)?),
+        );
+        if last_placement.as_ref() == Some(&placement) {
+            return Ok(None);
+        }
+        *last_placement = Some(placement.clone());
+        Ok(Some(placement))
+    }
+
+    pub fn capture(&mut self) -> Result<Arc<Frame>, TranscendiaError> {
+        let sf = self.scale_factor;
+        let full_rect = physical(&self.region, sf);
+        let (capture, rect) = match &mut self.source {
+            CaptureSource::Monitor {
+                monitor,
+                region_capture_retry_at,
+            } => {
+                let region_capture = match region_capture_retry_at {
+                    Some(retry_at) if Instant::now() < *retry_at => None,
+                    _ => {
+                        let (x, y, w, h) = region_capture_rect(&self.region, sf);
+                        Some(monitor.capture_region(x, y, w, h))
+                    }
+                };
+                match region_capture {
+                    Some(Ok(image)) => {
+                        *region_capture_retry_at = None;
+                        let rect = (0, 0, image.width(), image.height());
+                        (image, rect)
+                    }
+                    Some(Err(err)) => {
+                        eprintln!(
+                            "Region capture failed, cropping full captures for now: {}",
+                            err
+                        );
+                        *region_capture_retry_at = Some(Instant::now() + REGION_CAPTURE_RETRY);
+                        (monitor.capture_image()?, full_rect)
+                    }
+                    None => (monitor.capture_image()?, full_rect),
+                }
+            }
+            CaptureSource::Window {
+                window,
+                last_placement,
+            } => {
+                let region = last_placement.as_ref().map_or(&self.region, |(_, r)| r);
+                (window.capture_image()?, physical(region, sf))
+            }
+        };
+
+        // A frame still held by the OCR stage is left to it, the capture gets a new one.
+        let mut frame = match self.recycled.take() {
+            Some(frame) if Arc::strong_count(&frame) == 1 => frame,
+            _ => Arc::new(Frame {
+                image: RgbImage::default(),
+                scale_factor: sf,
+            }),
+        };
+        // Never copies, the frame is not shared.
+        let target = Arc::make_mut(&mut frame);
+        target.scale_factor = sf;
+        copy_rgb(&capture, rect, &mut target.image);
+        self.recycled = Some(frame.clone());
+        Ok(frame)
+    }
+}
+
+/// Region in the units of `Monitor::capture_region`: points on macOS, where xcap works with
+/// the display bounds, physical pixels elsewhere.
+fn region_capture_rect(region: &Region, sf: f32) -> (u32, u32, u32, u32) {
+    if cfg!(target_os = "macos") {
+        physical(region, 1.0)
+    } else {
+        physical(region, sf)
+    }
+}
+
+/// Region in physical pixels, which is what xcap captures in.
+fn physical(region: &Region, sf: f32) -> (u32, u32, u32, u32) {
+    (
+        (region.x as f32 * sf) as u32,
+        (region.y as f32 * sf) as u32,
+        (region.w as f32 * sf) as u32,
+        (region.h as f32 * sf) as u32,
+    )
+}
+
+/// Copies `rect` of a capture without its alpha channel, clamped to the capture like a crop.
+/// `target` keeps its allocation while the size stays the same.
+fn copy_rgb(capture: &RgbaImage, (x, y, w, h): (u32, u32, u32, u32), target: &mut RgbImage) {
+    let x = x.min(capture.width());
+    let y = y.min(capture.height());
+    let w = w.min(capture.width() - x);
+    let h = h.min(capture.height() - y);
+    if target.dimensions() != (w, h) {
+        *target = RgbImage::new(w, h);
+    }
+    for (target_x, target_y, pixel) in target.enumerate_pixels_mut() {
+        let [r, g, b, _] = capture.get_pixel(x + target_x, y + target_y).0;
+        *pixel = Rgb([r, g, b]);
+    }
+}
+
+/// Grayscale thumbnail of a frame, cheap enough to compare every tick.
+pub struct FrameSignature(Vec<u8>);
+
+impl FrameSignature {
+    pub fn new(image: &RgbImage) -> Self {
+        let thumbnail = resize(
+            &grayscale(image),
+            SIGNATURE_SIZE as u32,
+            SIGNATURE_SIZE as u32,
+            FilterType::Triangle,
+        );
+        Self(thumbnail.into_raw())
+    }
+
+    /// Mean absolute pixel difference of the tile that changed the most, from 0 (same frame)
+    /// to 1. Over the whole frame, a few changed words would average out to nothing.
+    pub fn difference(&self, other: &FrameSignature) -> f32 {
+        let tiles = SIGNATURE_SIZE / SIGNATURE_TILE;
+        let mut worst = 0;
+        for tile_y in 0..tiles {
+            for tile_x in 0..tiles {
+                let mut total = 0;
+                for y in tile_y * SIGNATURE_TILE..(tile_y + 1) * SIGNATURE_TILE {
+                    let row = y * SIGNATURE_SIZE + tile_x * SIGNATURE_TILE;
+                    total += self.0[row..row + SIGNATURE_TILE]
+                        .iter()
+                        .zip(&other.0[row..row + SIGNATURE_TILE])
+                        .map(|(a, b)| a.abs_diff(*b) as u32)
+                        .sum::<u32>();
+                }
+                worst = worst.max(total);
+            }
+        }
+        worst as f32 / ((SIGNATURE_TILE * SIGNATURE_TILE) as f32 * 255.0)
+    }
+}
+
+pub struct CaptureRequest {
+    /// Forgets the last frame sent, so this one is sent even if it barely changed.
+    pub fresh: bool,
+    pub frame_change_threshold: f32,
+}
+
+pub struct Capture {
+    /// New origin and clamped region of a followed window, when it moved or was resized since
+    /// the last capture.
+    pub moved_placement: Option<((f32, f32), Region)>,
+    /// `None` when the frame barely changed since the last one sent.
+    pub frame: Option<Arc<Frame>>,
+}
+
+/// Region capturer owned by a dedicated thread, so xcap, the pixel conversion and the frame
+/// signature never run on the async runtime. The thread stops when this handle is dropped.
+pub struct CaptureWorker {
+    requests: Sender<CaptureRequest>,
+    results: Receiver<CaptureResult>,
+    thread: JoinHandle<()>,
+}
+
+impl CaptureWorker {
+    pub fn spawn(
+        region_id: &str,
+        monitor: u32,
+        window: Option<WindowTarget>,
+        region: Region,
+    ) -> Result<Self, TranscendiaError> {
+        let (requests, mut request_rx) = channel::<CaptureRequest>(CAPTURE_QUEUE_SIZE);
+        let (result_tx, results) = channel::<CaptureResult>(CAPTURE_QUEUE_SIZE);
+
+        let thread = std::thread::Builder::new()
+            .name(format!("capture-{}", region_id))
+            .spawn(move || {
+                let mut state = CaptureState {
+                    monitor,
+                    window,
+                    region,
+                    capturer: None,
+                    last_signature: None,
+                };
+                while let Some(request) = request_rx.blocking_recv() {
+                    let result = state.capture(&request);
+                    if let Err(err) = &result {
+                        state.reset_after(err);
+                    }
+                    if result_tx.blocking_send(result).is_err() {
+                        break;
+                    }
+                }
+            })?;
+
+        Ok(Self {
+            requests,
+            results,
+            thread,
+        })
+    }
+
+    /// False once the thread exited, after a panic in xcap for instance.
+    pub fn is_alive(&self) -> bool {
+        !self.thread.is_finished()
+    }
+
+    pub async fn capture(&mut self, request: CaptureRequest) -> CaptureResult {
+        self.requests
+            .send(request)
+            .await
+            .map_err(|_| capture_stopped())?;
+        self.results.recv().await.ok_or_else(capture_stopped)?
+    }
+}
+
+fn capture_stopped() -> TranscendiaError {
+    TranscendiaError::ThreadStopped("capture")
+}
+
+/// Everything the capture thread keeps between two captures.
+struct CaptureState {
+    monitor: u32,
+    window: Option<WindowTarget>,
+    region: Region,
+    capturer: Option<RegionCapturer>,
+    last_signature: Option<FrameSignature>,
+}
+
+impl CaptureState {
+    fn capture(&mut self, request: &CaptureRequest) -> CaptureResult {
+        if request.fresh {
+            self.last_signature = None;
+        }
+        let capturer = match &mut self.capturer {
+            Some(capturer) => capturer,
+            None => self.capturer.insert(match &self.window {
+                Some(target) => {
+                    RegionCapturer::for_window(find_window(target)?, self.region.clone())?
+                }
+                None => RegionCapturer::new(find_monitor(self.monitor)?, self.region.clone())?,
+            }),
+        };
+
+        let moved_placement = capturer.moved_placement()?;
+        let frame = capturer.capture()?;
+        let signature = FrameSignature::new(&frame.image);
+        if let Some(last) = &self.last_signature {
+            if signature.difference(last) < request.frame_change_threshold {
+                return Ok(Capture {
+                    moved_placement,
+                    frame: None,
+                });
+            }
+        }
+        self.last_signature = Some(signature);
+
+        Ok(Capture {
+            moved_placement,
+            frame: Some(frame),
+        })
+    }
+
+    /// Drops the capturer when the error points at it, so it is rebuilt on the next capture.
+    fn reset_after(&mut self, err: &TranscendiaError) {
+        if let TranscendiaError::CannotGetMonitors
+        | TranscendiaError::MonitorNotFound(_)
+        | TranscendiaError::WindowNotFound(_)
+        | TranscendiaError::Capture(_) = err
+        {
+            self.capturer = None;
+        }
+        self.last_signature = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// White frame with a dark box standing for a word, placed and sized relative to the frame.
+    fn frame(width: u32, height: u32, word: Option<(f32, f32)>) -> RgbImage {
+        let mut image = RgbImage::from_pixel(width, height, Rgb([255, 255, 255]));
+        if let Some((x, y)) = word {
+            let (x, y) = ((x * width as f32) as u32, (y * height as f32) as u32);
+            for py in y..y + height / 10 {
+                for px in x..x + width / 20 {
+                    image.put_pixel(px, py, Rgb([20, 20, 20]));
+                }
+            }
+        }
+        image
+    }
+
+    #[test]
+    fn identical_frames_do_not_differ() {
+        let image = frame(400, 100, Some((0.5, 0.4)));
+
+        let difference = FrameSignature::new(&image).difference(&FrameSignature::new(&image));
+
+        assert_eq!(difference, 0.0);
+    }
+
+    #[test]
+    fn a_small_localized_change_stands_out() {
+        let before = FrameSignature::new(&frame(400, 100, None));
+        let after = FrameSignature::new(&frame(400, 100, Some((0.5, 0.4))));
+
+        let difference = after.difference(&before);
+
+        assert!(difference > 0.5, "{}", difference);
+    }
+
+    #[test]
+    fn frames_of_different_sizes_compare_by_content() {
+        let small = FrameSignature::new(&frame(400, 100, Some((0.5, 0.4))));
+        let large = FrameSignature::new(&frame(800, 200, Some((0.5, 0.4))));
+        let moved = FrameSignature::new(&frame(800, 200, Some((0.1, 0.4))));
+
+        let (resized, elsewhere) = (large.difference(&small), moved.difference(&small));
+
+        assert!(resized < 0.01, "{}", resized);
+        assert!(elsewhere > 0.5, "{}", elsewhere);
+    }
+}
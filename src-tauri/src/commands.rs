@@ -14,24 +14,53 @@
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
-use crate::config::{Config, ConfigState};
-use crate::errors::TranscendiaError;
+use crate::capture::{find_monitor, find_window, monitor_origin, region_origin};
+use crate::config::{Config, ConfigState, Region, WindowTarget};
+use crate::downloads::{DownloadManager, DownloadState};
+use crate::errors::{lock, TranscendiaError};
 use crate::events::Events;
-use crate::translate_runtime::{start_translate_runtime, stop_translate_runtime, TranslateRuntime};
+use crate::hotkeys::register_hotkeys;
+use crate::model_manifest::{self, ModelStatus};
+use crate::ocr_models::{
+    check_for_ocr_models, check_for_translation_model, get_models_folder, import_model,
+    install_model, notify_if_models_ready,
+};
+use crate::profiles::{self, Profile, ProfilesList, ProfilesState};
+use crate::runtime_stats::RegionStats;
+use crate::translate_runtime::{
+    start_translate_runtime, stop_translate_runtime, RuntimeMode, TranslateRuntime,
+};
 use crate::windows::{
-    create_config_window, create_overlay_window, create_select_region_window, edit_overlay,
+    create_config_window, create_download_window, create_overlay_window,
+    create_select_region_window, edit_overlay, overlay_label,
 };
 use serde::Serialize;
-use std::sync::atomic::Ordering;
+use std::path::Path;
+use std::sync::Mutex;
 use tauri::{AppHandle, Emitter, Manager};
 use xcap::Monitor;
 
+#[derive(Default)]
+pub struct Selection {
+    /// Region currently being drawn in the select window.
+    pub pending: Option<String>,
+    /// Region the user last selected or edited, the one the reselect hotkey targets.
+    pub last_edited: Option<String>,
+}
+
+#[derive(Default)]
+pub struct SelectionState(pub Mutex<Selection>);
+
+impl SelectionState {
+    pub fn edited(&self, region_id: &str) -> Result<(), TranscendiaError> {
+        lock(&self.0, "region selection")?.last_edited = Some(region_id.to_string());
+        Ok(())
+    }
+}
+
 #[tauri::command]
 pub fn get_config(config: tauri::State<'_, ConfigState>) -> Result<Config, TranscendiaError> {
-    let config = config
-        .0
-        .lock()
-        .map_err(|_| TranscendiaError::CannotLoadConfig)?;
+    let config = lock(&config.0, "config")?;
     Ok(config.clone())
 }
 
@@ -40,30 +69,98 @@ pub async fn set_config(
     app_handle: AppHandle,
     config: tauri::State<'_, ConfigState>,
     runtime: tauri::State<'_, TranslateRuntime>,
-    new_config: Config,
+    profiles_state: tauri::State<'_, ProfilesState>,
+    selection: tauri::State<'_, SelectionState>,
+    mut new_config: Config,
     refresh_w_overlay: bool,
 ) -> Result<(), TranscendiaError> {
-    new_config.save(&app_handle);
+    let (previous, edited) = {
+        let config = lock(&config.0, "config")?;
+        let edited = new_config.edited_region(&config).map(|r| r.id.clone());
+        (config.translator.clone(), edited)
+    };
+    if let Some(region_id) = edited {
+        selection.edited(&region_id)?;
+    }
+    // The key of the previous backend is kept too, the UI drops it when switching.
+    new_config.remember_api_key(&previous);
+    new_config.remember_api_key(&new_config.translator.clone());
+    save_config(&app_handle, &profiles_state, &new_config)?;
+    apply_config(
+        &app_handle,
+        &config,
+        &runtime,
+        new_config,
+        refresh_w_overlay,
+    )?;
+    f_s_r(app_handle, config, runtime, false)
+}
 
-    let mut config = config
-        .0
-        .lock()
-        .map_err(|_| TranscendiaError::CannotSaveConfig)?;
+/// Writes the global config and keeps the active profile in sync with it.
+fn save_config(
+    app_handle: &AppHandle,
+    profiles_state: &ProfilesState,
+    config: &Config,
+) -> Result<(), TranscendiaError> {
+    config.save(app_handle)?;
+    if let Some(profile) = profiles_state.active()? {
+        profiles::save_profile(app_handle, &profile, &Profile::from_config(config))?;
+    }
+    Ok(())
+}
+
+/// Swaps the running config, restarting only the regions whose capture area changed.
+fn apply_config(
+    app_handle: &AppHandle,
+    config: &ConfigState,
+    runtime: &TranslateRuntime,
+    new_config: Config,
+    refresh_w_overlay: bool,
+) -> Result<(), TranscendiaError> {
+    let mut config = lock(&config.0, "config")?;
+    let mirror_changed = config.model_mirror != new_config.model_mirror;
+    if mirror_changed || config.translator != new_config.translator {
+        check_for_translation_model(app_handle, &new_config);
+    }
+    if mirror_changed || config.ocr_models != new_config.ocr_models {
+        check_for_ocr_models(app_handle, &new_config);
+    }
+    if config.hotkeys != new_config.hotkeys {
+        register_hotkeys(app_handle, &new_config.hotkeys);
+    }
+    for old in &config.regions {
+        match new_config.find_region(&old.id) {
+            None => {
+                stop_translate_runtime(runtime, &old.id)?;
+                if let Some(w) = app_handle.get_webview_window(&overlay_label(&old.id)) {
+                    w.close()?;
+                }
+            }
+            Some(new)
+                if new.region != old.region
+                    || new.monitor != old.monitor
+                    || new.window != old.window =>
+            {
+                stop_translate_runtime(runtime, &old.id)?;
+            }
+            Some(_) => {}
+        }
+    }
     *config = new_config;
 
-    runtime.interval.store(config.interval, Ordering::SeqCst);
+    for region in &config.regions {
+        runtime.update(region, &config)?;
+    }
 
     if refresh_w_overlay {
-        let windows = app_handle.webview_windows();
-        let window = windows.values().find(|x| x.label() == "overlay");
-        if let (Some(w), Some(r)) = (window, config.region.clone()) {
-            edit_overlay(w, &r, config.monitor, config.blur_background)
-                .expect("Failed to edit overlay");
+        for region in &config.regions {
+            let window = app_handle.get_webview_window(&overlay_label(&region.id));
+            if let (Some(w), Some(r)) = (window, &region.region) {
+                edit_overlay(&w, r, region_origin(region)?, config.blur_background)?;
+            }
         }
     } else {
-        app_handle
-            .emit(Events::RefreshOverlay.as_str(), None::<bool>)
-            .expect("Failed to emit event");
+        app_handle.emit(Events::RefreshOverlay.as_str(), None::<bool>)?;
     }
 
     Ok(())
@@ -79,28 +176,105 @@ pub struct XCapMonitor {
 pub fn get_monitors() -> Result<Vec<XCapMonitor>, TranscendiaError> {
     Monitor::all()
         .map(|ms| {
-            ms.into_iter().map(|m| XCapMonitor {
-                name: m.name().unwrap(),
-                id: m.id().unwrap(),
-            }).collect()
+            ms.into_iter()
+                .filter_map(|m| {
+                    Some(XCapMonitor {
+                        name: m.name().ok()?,
+                        id: m.id().ok()?,
+                    })
+                })
+                .collect()
         })
         .map_err(|_| TranscendiaError::CannotGetMonitors)
 }
 
+#[derive(Debug, Serialize)]
+pub struct XCapWindow {
+    app_name: String,
+    title: String,
+}
+
+/// Visible windows of other applications, candidates for window-anchored capture.
 #[tauri::command]
-pub async fn select_region(
+pub fn get_windows() -> Result<Vec<XCapWindow>, TranscendiaError> {
+    let own_pid = std::process::id();
+    let windows = xcap::Window::all()?
+        .into_iter()
+        .filter(|w| w.pid().is_ok_and(|pid| pid != own_pid))
+        .filter(|w| !w.is_minimized().unwrap_or(true))
+        .filter_map(|w| {
+            Some(XCapWindow {
+                app_name: w.app_name().ok()?,
+                title: w.title().ok()?,
+            })
+        })
+        .filter(|w| !w.app_name.is_empty())
+        .collect();
+    Ok(windows)
+}
+
+/// Makes a region follow a window, or pins it back to its monitor, keeping it in place.
+#[tauri::command]
+pub async fn set_region_window(
     app_handle: AppHandle,
+    config: tauri::State<'_, ConfigState>,
     runtime: tauri::State<'_, TranslateRuntime>,
-    monitor: u32,
-) -> Result<(), tauri::Error> {
-    create_select_region_window(&app_handle, monitor)?;
+    profiles_state: tauri::State<'_, ProfilesState>,
+    selection: tauri::State<'_, SelectionState>,
+    region_id: String,
+    window: Option<WindowTarget>,
+) -> Result<Config, TranscendiaError> {
+    selection.edited(&region_id)?;
+    let mut new_config = lock(&config.0, "config")?.clone();
+    let Some(capture_region) = new_config.find_region_mut(&region_id) else {
+        return Ok(new_config);
+    };
+
+    let from = region_origin(capture_region)?;
+    if let Some(target) = &window {
+        capture_region.monitor = find_window(target)?.current_monitor()?.id()?;
+    }
+    capture_region.window = window;
+    let to = region_origin(capture_region)?;
+    capture_region.region = capture_region.region.as_ref().map(|r| r.rebase(from, to));
+
+    save_config(&app_handle, &profiles_state, &new_config)?;
+    apply_config(&app_handle, &config, &runtime, new_config.clone(), true)?;
+    f_s_r(app_handle, config, runtime, false)?;
+
+    Ok(new_config)
+}
+
+#[tauri::command]
+pub async fn select_region(
+    app_handle: AppHandle,
+    region_id: String,
+) -> Result<(), TranscendiaError> {
+    begin_region_selection(&app_handle, region_id)
+}
 
-    let windows = app_handle.webview_windows();
-    let window = windows.values().find(|x| x.label() == "overlay");
-    if let Some(w) = window {
+/// Opens the select window over the region's monitor and stops the region meanwhile.
+pub fn begin_region_selection(
+    app_handle: &AppHandle,
+    region_id: String,
+) -> Result<(), TranscendiaError> {
+    let monitor = {
+        let config = app_handle.state::<ConfigState>();
+        let config = lock(&config.0, "config")?;
+        config
+            .find_region(&region_id)
+            .map(|r| r.monitor)
+            .unwrap_or(0)
+    };
+    create_select_region_window(app_handle, monitor)?;
+
+    if let Some(w) = app_handle.get_webview_window(&overlay_label(&region_id)) {
         w.close()?;
-        stop_translate_runtime(&runtime);
+        stop_translate_runtime(&app_handle.state::<TranslateRuntime>(), &region_id)?;
     }
+    let mut selection = lock(&app_handle.state::<SelectionState>().0, "region selection")?;
+    selection.last_edited = Some(region_id.clone());
+    selection.pending = Some(region_id);
 
     Ok(())
 }
@@ -110,7 +284,22 @@ pub async fn finish_select_region(
     app_handle: AppHandle,
     config: tauri::State<'_, ConfigState>,
     runtime: tauri::State<'_, TranslateRuntime>,
-) -> Result<(), tauri::Error> {
+    profiles_state: tauri::State<'_, ProfilesState>,
+    selection: tauri::State<'_, SelectionState>,
+    region: Region,
+) -> Result<(), TranscendiaError> {
+    let pending = lock(&selection.0, "region selection")?.pending.take();
+    if let Some(region_id) = pending {
+        let mut config = lock(&config.0, "config")?;
+        if let Some(capture_region) = config.find_region_mut(&region_id) {
+            // The selection is drawn over the monitor, followed windows store it relative to them.
+            let from = monitor_origin(&find_monitor(capture_region.monitor)?)?;
+            let to = region_origin(capture_region)?;
+            capture_region.region = Some(region.rebase(from, to));
+        }
+        save_config(&app_handle, &profiles_state, &config)?;
+    }
+
     f_s_r(app_handle, config, runtime, true)
 }
 
@@ -119,17 +308,28 @@ pub fn f_s_r(
     config: tauri::State<'_, ConfigState>,
     runtime: tauri::State<'_, TranslateRuntime>,
     create_config: bool,
-) -> Result<(), tauri::Error> {
-    let config = config.0.lock().expect("Cannot read config");
-    if let Some(region) = &config.region {
-        start_translate_runtime(
-            &app_handle,
-            &runtime,
-            config.monitor.clone(),
-            region.clone(),
-            config.lang.clone(),
-        );
-        create_overlay_window(&app_handle, region, config.monitor, config.blur_background)?;
+) -> Result<(), TranscendiaError> {
+    let config = lock(&config.0, "config")?;
+    for capture_region in &config.regions {
+        if let (Some(region), false) = (
+            &capture_region.region,
+            runtime.is_running(&capture_region.id)?,
+        ) {
+            start_translate_runtime(&app_handle, &runtime, capture_region, &config)?;
+            let origin = region_origin(capture_region)?;
+            match app_handle.get_webview_window(&overlay_label(&capture_region.id)) {
+                Some(w) => edit_overlay(&w, region, origin, config.blur_background)?,
+                None => {
+                    create_overlay_window(
+                        &app_handle,
+                        &capture_region.id,
+                        region,
+                        origin,
+                        config.blur_background,
+                    )?;
+                }
+            }
+        }
     }
 
     if create_config {
@@ -139,13 +339,256 @@ pub fn f_s_r(
     Ok(())
 }
 
+/// Starts the regions waiting for models, on `Events::ModelsReady`.
+pub fn models_ready(app_handle: &AppHandle) -> Result<(), TranscendiaError> {
+    f_s_r(
+        app_handle.clone(),
+        app_handle.state::<ConfigState>(),
+        app_handle.state::<TranslateRuntime>(),
+        false,
+    )?;
+    if let Some(w) = app_handle.get_webview_window("downloader") {
+        w.close()?;
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_downloads(
+    downloads: tauri::State<'_, DownloadManager>,
+) -> Result<Vec<DownloadState>, TranscendiaError> {
+    downloads.states()
+}
+
+/// Stops every download, the downloader window stays open to retry them.
+#[tauri::command]
+pub fn cancel_downloads(
+    downloads: tauri::State<'_, DownloadManager>,
+) -> Result<(), TranscendiaError> {
+    downloads.cancel_all()
+}
+
 #[tauri::command]
-pub async fn download_finish(
+pub fn retry_download(
+    app_handle: AppHandle,
+    downloads: tauri::State<'_, DownloadManager>,
+    id: String,
+) -> Result<(), TranscendiaError> {
+    downloads.retry(&app_handle, &id)
+}
+
+#[tauri::command]
+pub fn list_profiles(
+    app_handle: AppHandle,
+    profiles_state: tauri::State<'_, ProfilesState>,
+) -> Result<ProfilesList, TranscendiaError> {
+    Ok(ProfilesList {
+        data: profiles_state.data()?,
+        profiles: profiles::list_profiles(&app_handle),
+    })
+}
+
+/// Saves the current config under a new name and makes it the active profile.
+#[tauri::command]
+pub fn create_profile(
     app_handle: AppHandle,
     config: tauri::State<'_, ConfigState>,
-    runtime: tauri::State<'_, TranslateRuntime>,
-) -> Result<(), tauri::Error> {
+    profiles_state: tauri::State<'_, ProfilesState>,
+    name: String,
+) -> Result<(), TranscendiaError> {
+    let name = name.trim().to_string();
+    let profile = Profile::from_config(&lock(&config.0, "config")?);
+    profiles::create_profile(&app_handle, &name, &profile)?;
+    profiles_state.set_active(&app_handle, Some(name))?;
+    app_handle.emit(Events::ProfilesChanged.as_str(), None::<bool>)?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn clone_profile(
+    app_handle: AppHandle,
+    source: String,
+    name: String,
+) -> Result<(), TranscendiaError> {
+    let profile = profiles::load_profile(&app_handle, &source)?;
+    profiles::create_profile(&app_handle, name.trim(), &profile)?;
+    app_handle.emit(Events::ProfilesChanged.as_str(), None::<bool>)?;
+
+    Ok(())
+}
+
+/// The global config is kept as is, only the saved profile goes away.
+#[tauri::command]
+pub fn delete_profile(
+    app_handle: AppHandle,
+    profiles_state: tauri::State<'_, ProfilesState>,
+    name: String,
+) -> Result<(), TranscendiaError> {
+    profiles::delete_profile(&app_handle, &name)?;
+    profiles_state.update(&app_handle, |data| {
+        data.bindings.remove(&name);
+        if data.active.as_deref() == Some(name.as_str()) {
+            data.active = None;
+        }
+    })?;
+    app_handle.emit(Events::ProfilesChanged.as_str(), None::<bool>)?;
+
+    Ok(())
+}
+
+/// Binds a profile to app names or window titles for automatic switching.
+#[tauri::command]
+pub fn set_profile_bindings(
+    app_handle: AppHandle,
+    profiles_state: tauri::State<'_, ProfilesState>,
+    name: String,
+    bindings: Vec<String>,
+) -> Result<(), TranscendiaError> {
+    let bindings: Vec<String> = bindings
+        .into_iter()
+        .map(|b| b.trim().to_string())
+        .filter(|b| !b.is_empty())
+        .collect();
+    profiles_state.update(&app_handle, |data| {
+        if bindings.is_empty() {
+            data.bindings.remove(&name);
+        } else {
+            data.bindings.insert(name, bindings);
+        }
+    })?;
+    app_handle.emit(Events::ProfilesChanged.as_str(), None::<bool>)?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn set_auto_switch(
+    app_handle: AppHandle,
+    profiles_state: tauri::State<'_, ProfilesState>,
+    enabled: bool,
+) -> Result<(), TranscendiaError> {
+    profiles_state.update(&app_handle, |data| data.auto_switch = enabled)?;
+    app_handle.emit(Events::ProfilesChanged.as_str(), None::<bool>)?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn activate_profile(app_handle: AppHandle, name: String) -> Result<(), TranscendiaError> {
+    switch_profile(&app_handle, &name)
+}
+
+/// Loads a profile into the global config and moves the running regions over to it.
+pub fn switch_profile(app_handle: &AppHandle, name: &str) -> Result<(), TranscendiaError> {
+    let profile = profiles::load_profile(app_handle, name)?;
+    let config = app_handle.state::<ConfigState>();
+    let new_config = profile.apply(&lock(&config.0, "config")?);
+    new_config.save(app_handle)?;
+
+    let runtime = app_handle.state::<TranslateRuntime>();
+    apply_config(app_handle, &config, &runtime, new_config, true)?;
+    app_handle
+        .state::<ProfilesState>()
+        .set_active(app_handle, Some(name.to_string()))?;
     f_s_r(app_handle.clone(), config, runtime, false)?;
 
+    app_handle.emit(Events::RefreshOverlay.as_str(), None::<bool>)?;
+    app_handle.emit(Events::ProfilesChanged.as_str(), None::<bool>)?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_runtime_mode(
+    runtime: tauri::State<'_, TranslateRuntime>,
+) -> Result<RuntimeMode, TranscendiaError> {
+    runtime.mode()
+}
+
+#[tauri::command]
+pub fn set_runtime_mode(app_handle: AppHandle, mode: RuntimeMode) -> Result<(), TranscendiaError> {
+    change_runtime_mode(&app_handle, mode)
+}
+
+#[tauri::command]
+pub fn get_runtime_stats(
+    runtime: tauri::State<'_, TranslateRuntime>,
+) -> Result<Vec<RegionStats>, TranscendiaError> {
+    runtime.stats()
+}
+
+/// Captures and translates every region once, whatever the current mode.
+#[tauri::command]
+pub fn translate_once(runtime: tauri::State<'_, TranslateRuntime>) -> Result<(), TranscendiaError> {
+    runtime.retranslate()
+}
+
+#[tauri::command]
+pub fn list_models(app_handle: AppHandle) -> Vec<ModelStatus> {
+    model_manifest::list_models(&get_models_folder(&app_handle))
+}
+
+/// Downloads a model from the manifest, progress shows in the download window.
+#[tauri::command]
+pub fn download_ocr_model(
+    app_handle: AppHandle,
+    config: tauri::State<'_, ConfigState>,
+    id: String,
+) -> Result<(), TranscendiaError> {
+    let models_folder = get_models_folder(&app_handle);
+    let entry = model_manifest::find_any_model(&models_folder, &id)?;
+    if entry.is_installed(&models_folder) {
+        return Ok(());
+    }
+
+    let mirror = lock(&config.0, "config")?.model_mirror.clone();
+    install_model(&app_handle, &models_folder, &entry, mirror.as_deref())?;
+    if app_handle.get_webview_window("downloader").is_none() {
+        create_download_window(&app_handle)?;
+    }
+    Ok(())
+}
+
+/// Installs a model from a `.rten` file on disk, replacing the installed one if any.
+#[tauri::command]
+pub async fn import_ocr_model(
+    app_handle: AppHandle,
+    id: String,
+    path: String,
+) -> Result<(), TranscendiaError> {
+    let models_folder = get_models_folder(&app_handle);
+    let entry = model_manifest::find_any_model(&models_folder, &id)?;
+    import_model(&models_folder, &entry, Path::new(&path)).await?;
+    notify_if_models_ready(&app_handle);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn delete_ocr_model(
+    app_handle: AppHandle,
+    config: tauri::State<'_, ConfigState>,
+    id: String,
+) -> Result<(), TranscendiaError> {
+    {
+        let config = lock(&config.0, "config")?;
+        if config.ocr_models.detection == id || config.ocr_models.recognition == id {
+            return Err(TranscendiaError::ModelInUse(id));
+        }
+    }
+
+    let models_folder = get_models_folder(&app_handle);
+    let entry = model_manifest::find_any_model(&models_folder, &id)?;
+    model_manifest::delete_model(&models_folder, &entry)
+}
+
+pub fn change_runtime_mode(
+    app_handle: &AppHandle,
+    mode: RuntimeMode,
+) -> Result<(), TranscendiaError> {
+    app_handle.state::<TranslateRuntime>().set_mode(mode)?;
+    app_handle.emit(Events::RuntimeModeChanged.as_str(), mode)?;
+
     Ok(())
 }
@@ -1,13 +1,23 @@
 // Copyright © 2025 Nantsa Montillet
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
+use crate::errors::TranscendiaError;
+use crate::translators::{AUTO_DETECT_LANG, GOOGLE_BASE_URL};
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
 use tauri::{AppHandle, Manager};
 
 const GLOBAL_CONFIG_FILE: &str = "global_config.json";
+const MAIN_REGION_ID: &str = "main";
+
+/// Each entry upgrades a config from version `index` to `index + 1`.
+const MIGRATIONS: &[fn(&mut Map<String, Value>)] = &[upgrade_single_region, upgrade_interval_ms];
+pub const CONFIG_VERSION: u32 = MIGRATIONS.len() as u32;
 
 pub fn get_config_path(app_handle: &AppHandle) -> PathBuf {
     let mut path = app_handle
@@ -18,7 +28,7 @@ pub fn get_config_path(app_handle: &AppHandle) -> PathBuf {
     path
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct Region {
     pub x: u32,
     pub y: u32,
@@ -26,61 +36,590 @@ pub struct Region {
     pub h: u32,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
-pub struct Config {
+impl Region {
+    /// Same rectangle expressed relative to another origin, clamped to stay positive.
+    pub fn rebase(&self, from: (f32, f32), to: (f32, f32)) -> Region {
+        Region {
+            x: (self.x as f32 + from.0 - to.0).max(0.0) as u32,
+            y: (self.y as f32 + from.1 - to.1).max(0.0) as u32,
+            ..self.clone()
+        }
+    }
+
+    /// Part of the rectangle inside an area of `size` starting at its origin, at least one pixel.
+    pub fn clamp_to(&self, (w, h): (f32, f32)) -> Region {
+        let (w, h) = (w.max(1.0) as u32, h.max(1.0) as u32);
+        let (x, y) = (self.x.min(w - 1), self.y.min(h - 1));
+        Region {
+            x,
+            y,
+            w: self.w.clamp(1, w - x),
+            h: self.h.clamp(1, h - y),
+        }
+    }
+}
+
+/// Window a capture region follows, its region is then relative to the window.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct WindowTarget {
+    pub app_name: String,
+    pub title: String,
+}
+
+/// Global shortcuts in the `Ctrl+Shift+P` format, an empty entry is not registered.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct Hotkeys {
+    pub pause_resume: Option<String>,
+    pub reselect_region: Option<String>,
+    pub toggle_overlay: Option<String>,
+    pub retranslate: Option<String>,
+    pub copy_translation: Option<String>,
+    pub cycle_profile: Option<String>,
+}
+
+/// Ids of the detection and recognition models from the model manifest.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct OcrModels {
+    pub detection: String,
+    pub recognition: String,
+}
+
+impl Default for OcrModels {
+    fn default() -> Self {
+        Self {
+            detection: "text-detection".to_string(),
+            recognition: "text-recognition".to_string(),
+        }
+    }
+}
+
+fn default_source_lang() -> String {
+    AUTO_DETECT_LANG.to_string()
+}
+
+fn default_google_url() -> String {
+    GOOGLE_BASE_URL.to_string()
+}
+
+fn default_similarity_threshold() -> f32 {
+    0.9
+}
+
+fn default_frame_change_threshold() -> f32 {
+    0.01
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum TranslatorConfig {
+    /// Public gtx endpoint, the URL only changes to point at a proxy or a mock server.
+    Google {
+        #[serde(default = "default_google_url")]
+        url: String,
+    },
+    DeepL {
+        api_key: String,
+        api_url: String,
+    },
+    LibreTranslate {
+        url: String,
+        api_key: Option<String>,
+    },
+    OpenAi {
+        url: String,
+        api_key: Option<String>,
+        model: String,
+    },
+    Offline {
+        model: String,
+    },
+}
+
+impl Default for TranslatorConfig {
+    fn default() -> Self {
+        TranslatorConfig::Google {
+            url: default_google_url(),
+        }
+    }
+}
+
+impl TranslatorConfig {
+    /// Name of the backend, as in the serialized `kind`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            TranslatorConfig::Google { .. } => "google",
+            TranslatorConfig::DeepL { .. } => "deepl",
+            TranslatorConfig::LibreTranslate { .. } => "libretranslate",
+            TranslatorConfig::OpenAi { .. } => "openai",
+            TranslatorConfig::Offline { .. } => "offline",
+        }
+    }
+
+    pub fn api_key(&self) -> Option<&str> {
+        match self {
+            TranslatorConfig::DeepL { api_key, .. } => Some(api_key.as_str()),
+            TranslatorConfig::LibreTranslate { api_key, .. }
+            | TranslatorConfig::OpenAi { api_key, .. } => api_key.as_deref(),
+            TranslatorConfig::Google { .. } | TranslatorConfig::Offline { .. } => None,
+        }
+        .filter(|key| !key.is_empty())
+    }
+
+    pub fn set_api_key(&mut self, key: Option<String>) {
+        match self {
+            TranslatorConfig::DeepL { api_key, .. } => *api_key = key.unwrap_or_default(),
+            TranslatorConfig::LibreTranslate { api_key, .. }
+            | TranslatorConfig::OpenAi { api_key, .. } => *api_key = key,
+            TranslatorConfig::Google { .. } | TranslatorConfig::Offline { .. } => {}
+        }
+    }
+
+    /// Identifies the service producing translations, credentials excluded.
+    pub fn cache_id(&self) -> String {
+        match self {
+            // Same id as before the URL could be changed, so existing caches stay valid.
+            TranslatorConfig::Google { url } if url == GOOGLE_BASE_URL => "google".to_string(),
+            TranslatorConfig::Google { url } => format!("google:{}", url),
+            TranslatorConfig::DeepL { api_url, .. } => format!("deepl:{}", api_url),
+            TranslatorConfig::LibreTranslate { url, .. } => format!("libretranslate:{}", url),
+            TranslatorConfig::OpenAi { url, model, .. } => format!("openai:{}:{}", url, model),
+            TranslatorConfig::Offline { model } => format!("offline:{}", model),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct CaptureRegion {
+    pub id: String,
+    pub name: String,
     pub region: Option<Region>,
+    pub window: Option<WindowTarget>,
     pub monitor: u32,
+    /// Time between the start of two captures.
+    pub interval_ms: u32,
+    /// Captures faster while the text changes and slower while the region is static.
+    pub adaptive: bool,
+    pub lang: String,
+    pub source_lang: String,
+}
+
+impl CaptureRegion {
+    pub fn new(id: &str, name: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            name: name.to_string(),
+            region: None,
+            window: None,
+            monitor: 0,
+            interval_ms: 1000,
+            adaptive: false,
+            lang: "en".to_string(),
+            source_lang: default_source_lang(),
+        }
+    }
+}
+
+impl Default for CaptureRegion {
+    fn default() -> Self {
+        CaptureRegion::new(MAIN_REGION_ID, "Main")
+    }
+}
+
+/// Missing fields take their default value, so adding a field never needs a migration.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Config {
+    pub version: u32,
+    pub regions: Vec<CaptureRegion>,
     pub text_color: String,
     pub text_align: String,
     pub text_size: u16,
     pub background_color: String,
     pub blur_background: bool,
-    pub interval: u8,
-    pub lang: String,
+    pub preserve_layout: bool,
+    pub similarity_threshold: f32,
+    pub frame_change_threshold: f32,
+    pub translator: TranslatorConfig,
+    /// API keys by translator kind. Profiles are saved without keys and take them from here.
+    pub api_keys: BTreeMap<String, String>,
+    pub ocr_models: OcrModels,
+    /// Base URL used instead of the default model hosts, laid out like the models folder.
+    /// Accepts `file://` and plain `http://` URLs for offline machines and internal mirrors.
+    pub model_mirror: Option<String>,
+    pub hotkeys: Hotkeys,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
-            region: None,
-            monitor: 0,
+            version: CONFIG_VERSION,
+            regions: vec![CaptureRegion::new(MAIN_REGION_ID, "Main")],
             text_align: "C:C".to_string(),
             text_color: "#FFFFFF".to_string(),
             text_size: 16,
             background_color: "#00000066".to_string(),
             blur_background: true,
-            interval: 1,
-            lang: "en".to_string(),
+            preserve_layout: false,
+            similarity_threshold: default_similarity_threshold(),
+            frame_change_threshold: default_frame_change_threshold(),
+            translator: TranslatorConfig::default(),
+            api_keys: BTreeMap::new(),
+            ocr_models: OcrModels::default(),
+            model_mirror: None,
+            hotkeys: Hotkeys::default(),
         }
     }
 }
 
 impl Config {
+    /// Never fails: unreadable or invalid files are backed up and replaced by the defaults.
     pub fn load(app: &AppHandle) -> Self {
         let config_path = get_config_path(app);
+        if !config_path.exists() {
+            let config = Config::default();
+            if let Err(err) = config.save(app) {
+                eprintln!("{}", err);
+            }
+            return config;
+        }
 
-        let config = if !config_path.exists() {
-            let parent = config_path.parent().unwrap();
-            if !parent.exists() {
-                fs::create_dir_all(parent).expect("Could not create config dir");
+        match read_config(&config_path) {
+            Ok((config, migrated)) => {
+                if migrated {
+                    if let Err(err) = config.save(app) {
+                        eprintln!("{}", err);
+                    }
+                }
+                config
             }
+            Err(err) => {
+                eprintln!(
+                    "Invalid {}, resetting to defaults: {}",
+                    GLOBAL_CONFIG_FILE, err
+                );
+                backup_corrupt_config(&config_path);
+                let config = Config::default();
+                if let Err(err) = config.save(app) {
+                    eprintln!("{}", err);
+                }
+                config
+            }
+        }
+    }
 
-            let data = serde_json::to_string(&Config::default())
-                .expect("Could not serialize default config");
-            fs::write(config_path, &data).expect("Could not write config.json");
-            data
-        } else {
-            fs::read_to_string(config_path).expect("Could not read config.json")
-        };
-        let config =
-            serde_json::from_str::<Config>(&config).expect("Could not deserialize config.json");
-        config
+    pub fn find_region(&self, id: &str) -> Option<&CaptureRegion> {
+        self.regions.iter().find(|r| r.id == id)
     }
 
-    pub fn save(&self, app: &AppHandle) {
-        let config = serde_json::to_string(self).expect("Could not stringify config");
-        fs::write(get_config_path(app), &config).expect("Could not write config.json");
+    pub fn find_region_mut(&mut self, id: &str) -> Option<&mut CaptureRegion> {
+        self.regions.iter_mut().find(|r| r.id == id)
+    }
+
+    /// First region added or changed since `previous`.
+    pub fn edited_region(&self, previous: &Config) -> Option<&CaptureRegion> {
+        self.regions
+            .iter()
+            .find(|r| previous.find_region(&r.id) != Some(*r))
+    }
+
+    /// Reads a config other than the global one, such as a profile, running the migrations.
+    pub fn read_file(path: &Path) -> Result<Self, TranscendiaError> {
+        read_config(path)
+            .map(|(config, _)| config)
+            .map_err(TranscendiaError::InvalidConfig)
+    }
+
+    /// Keeps the key of `translator` for the profiles selecting that backend later.
+    pub fn remember_api_key(&mut self, translator: &TranslatorConfig) {
+        if let Some(key) = translator.api_key() {
+            self.api_keys
+                .insert(translator.kind().to_string(), key.to_string());
+        }
+    }
+
+    pub fn save(&self, app: &AppHandle) -> Result<(), TranscendiaError> {
+        self.save_file(&get_config_path(app))
+    }
+
+    pub fn save_file(&self, path: &Path) -> Result<(), TranscendiaError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|_| TranscendiaError::CannotSaveConfig)?;
+        }
+        let config = serde_json::to_string(self).map_err(|_| TranscendiaError::CannotSaveConfig)?;
+        fs::write(path, &config).map_err(|_| TranscendiaError::CannotSaveConfig)
+    }
+}
+
+/// Returns the parsed config and whether it was upgraded from an older schema.
+fn read_config(path: &Path) -> Result<(Config, bool), String> {
+    let data = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let mut value = serde_json::from_str::<Value>(&data).map_err(|e| e.to_string())?;
+    let root = value
+        .as_object_mut()
+        .ok_or_else(|| "root is not an object".to_string())?;
+
+    let version = root.get("version").and_then(Value::as_u64).unwrap_or(0) as usize;
+    for migration in MIGRATIONS.iter().skip(version) {
+        migration(root);
+    }
+    let migrated = version < MIGRATIONS.len();
+    if migrated {
+        root.insert("version".to_string(), json!(CONFIG_VERSION));
+    }
+
+    let config = serde_json::from_value::<Config>(value).map_err(|e| e.to_string())?;
+    Ok((config, migrated))
+}
+
+/// Keeps the broken file next to the config so hand edits can be recovered.
+fn backup_corrupt_config(path: &Path) {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let backup = path.with_extension(format!("json.{}.bak", timestamp));
+    match fs::rename(path, &backup) {
+        Ok(()) => eprintln!("Previous config saved to {:?}", backup),
+        Err(err) => eprintln!("Could not back up config: {}", err),
+    }
+}
+
+/// v0 -> v1: configs written before multi-region support stored one region inline in the root.
+fn upgrade_single_region(root: &mut Map<String, Value>) {
+    if root.contains_key("regions") {
+        return;
+    }
+
+    let mut main = json!(CaptureRegion::new(MAIN_REGION_ID, "Main"));
+    for key in ["region", "monitor", "interval", "lang", "source_lang"] {
+        if let Some(value) = root.remove(key) {
+            main[key] = value;
+        }
+    }
+    root.insert("regions".to_string(), json!([main]));
+}
+
+/// v1 -> v2: region intervals were whole seconds stored in `interval`.
+fn upgrade_interval_ms(root: &mut Map<String, Value>) {
+    let Some(regions) = root.get_mut("regions").and_then(Value::as_array_mut) else {
+        return;
+    };
+
+    for region in regions.iter_mut().filter_map(Value::as_object_mut) {
+        if let Some(seconds) = region.remove("interval").and_then(|v| v.as_u64()) {
+            region.insert(
+                "interval_ms".to_string(),
+                json!(seconds.saturating_mul(1000).min(u32::MAX as u64)),
+            );
+        }
     }
 }
 
 pub struct ConfigState(pub Mutex<Config>);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fresh folder for one test, the tests run in parallel.
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "transcendia-config-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write_config(name: &str, data: &Value) -> PathBuf {
+        let path = test_dir(name).join(GLOBAL_CONFIG_FILE);
+        fs::write(&path, data.to_string()).unwrap();
+        path
+    }
+
+    fn object(value: Value) -> Map<String, Value> {
+        value.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn migrates_a_v0_file_to_the_current_version() {
+        let path = write_config(
+            "v0",
+            &json!({
+                "region": {"x": 10, "y": 20, "w": 300, "h": 40},
+                "monitor": 1,
+                "interval": 3,
+                "lang": "fr",
+                "source_lang": "ja",
+                "text_size": 22
+            }),
+        );
+
+        let (config, migrated) = read_config(&path).unwrap();
+
+        assert!(migrated);
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(config.text_size, 22);
+        assert_eq!(config.regions.len(), 1);
+        let main = &config.regions[0];
+        assert_eq!(main.id, MAIN_REGION_ID);
+        assert_eq!(
+            main.region,
+            Some(Region {
+                x: 10,
+                y: 20,
+                w: 300,
+                h: 40
+            })
+        );
+        assert_eq!(main.monitor, 1);
+        assert_eq!(main.interval_ms, 3000);
+        assert_eq!(main.lang, "fr");
+        assert_eq!(main.source_lang, "ja");
+    }
+
+    #[test]
+    fn current_files_are_not_migrated() {
+        let path = write_config(
+            "current",
+            &json!({
+                "version": CONFIG_VERSION,
+                "regions": [{"id": "a", "interval_ms": 500}]
+            }),
+        );
+
+        let (config, migrated) = read_config(&path).unwrap();
+
+        assert!(!migrated);
+        assert_eq!(config.regions[0].interval_ms, 500);
+    }
+
+    #[test]
+    fn moves_the_inline_region_into_regions() {
+        let mut root = object(json!({"monitor": 2, "interval": 5, "text_size": 18}));
+
+        upgrade_single_region(&mut root);
+
+        assert!(!root.contains_key("monitor"));
+        assert!(!root.contains_key("interval"));
+        assert_eq!(root["text_size"], 18);
+        let main = &root["regions"][0];
+        assert_eq!(main["id"], MAIN_REGION_ID);
+        assert_eq!(main["monitor"], 2);
+        assert_eq!(main["interval"], 5);
+    }
+
+    #[test]
+    fn keeps_existing_regions() {
+        let mut root = object(json!({"regions": [{"id": "a"}], "monitor": 2}));
+
+        upgrade_single_region(&mut root);
+
+        assert_eq!(root["regions"], json!([{"id": "a"}]));
+        assert_eq!(root["monitor"], 2);
+    }
+
+    #[test]
+    fn converts_intervals_to_milliseconds() {
+        let mut root = object(json!({
+            "regions": [{"id": "a", "interval": 2}, {"id": "b", "interval_ms": 750}]
+        }));
+
+        upgrade_interval_ms(&mut root);
+
+        assert_eq!(
+            root["regions"],
+            json!([{"id": "a", "interval_ms": 2000}, {"id": "b", "interval_ms": 750}])
+        );
+    }
+
+    #[test]
+    fn backs_up_a_corrupt_file() {
+        let path = write_config("corrupt", &json!(["not", "a", "config"]));
+
+        assert!(read_config(&path).is_err());
+        backup_corrupt_config(&path);
+
+        assert!(!path.exists());
+        let backups: Vec<_> = fs::read_dir(path.parent().unwrap())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .filter(|name| name.starts_with("global_config.json.") && name.ends_with(".bak"))
+            .collect();
+        assert_eq!(backups.len(), 1);
+    }
+
+    #[test]
+    fn missing_fields_take_their_default() {
+        let path = write_config(
+            "defaults",
+            &json!({
+                "version": CONFIG_VERSION,
+                "text_color": "#FF0000",
+                "regions": [{"id": "a", "name": "Dialogue"}]
+            }),
+        );
+
+        let (config, _) = read_config(&path).unwrap();
+        let defaults = Config::default();
+
+        assert_eq!(config.text_color, "#FF0000");
+        assert_eq!(config.text_size, defaults.text_size);
+        assert_eq!(config.translator, defaults.translator);
+        assert_eq!(config.hotkeys, defaults.hotkeys);
+        assert_eq!(config.ocr_models, defaults.ocr_models);
+        let region = &config.regions[0];
+        assert_eq!(region.name, "Dialogue");
+        assert_eq!(region.interval_ms, CaptureRegion::default().interval_ms);
+        assert_eq!(region.source_lang, default_source_lang());
+    }
+
+    #[test]
+    fn finds_the_added_or_changed_region() {
+        let previous = Config::default();
+        let mut config = previous.clone();
+        config.regions.push(CaptureRegion::new("b", "Second"));
+        assert_eq!(config.edited_region(&previous).unwrap().id, "b");
+
+        let previous = config.clone();
+        config.regions[1].lang = "fr".to_string();
+        assert_eq!(config.edited_region(&previous).unwrap().id, "b");
+
+        assert!(config.edited_region(&config).is_none());
+    }
+
+    #[test]
+    fn clamps_regions_to_a_smaller_area() {
+        let region = Region {
+            x: 100,
+            y: 50,
+            w: 300,
+            h: 40,
+        };
+
+        assert_eq!(region.clamp_to((1000.0, 1000.0)), region);
+        assert_eq!(
+            region.clamp_to((250.0, 70.0)),
+            Region {
+                x: 100,
+                y: 50,
+                w: 150,
+                h: 20
+            }
+        );
+        assert_eq!(
+            region.clamp_to((80.0, 30.0)),
+            Region {
+                x: 79,
+                y: 29,
+                w: 1,
+                h: 1
+            }
+        );
+    }
+}
@@ -0,0 +1,533 @@
+// Copyright © 2025 Nantsa Montillet
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::errors::{lock, report_error, TranscendiaError};
+use crate::events::Events;
+use crate::ocr_models::{get_models_folder, notify_if_models_ready};
+use futures_util::StreamExt;
+use reqwest::header::RANGE;
+use reqwest::{Client, StatusCode, Url};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::cmp::min;
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+use tokio::fs::{File, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{Notify, Semaphore};
+use tokio::time::sleep;
+
+/// Downloads are written next to their final path with this suffix, then renamed once complete.
+pub const PART_SUFFIX: &str = ".part";
+const MAX_PARALLEL_DOWNLOADS: usize = 2;
+const MAX_ATTEMPTS: u32 = 4;
+/// Delay before the second attempt, doubled for each of the next ones.
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(2);
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(30);
+/// Progress updates are throttled to this rate, status changes are always sent.
+const PROGRESS_EVENT_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DownloadStatus {
+    Queued,
+    Downloading,
+    Verifying,
+    Failed,
+    Cancelled,
+    Done,
+}
+
+impl DownloadStatus {
+    fn is_active(self) -> bool {
+        matches!(
+            self,
+            DownloadStatus::Queued | DownloadStatus::Downloading | DownloadStatus::Verifying
+        )
+    }
+}
+
+/// Sent with `Events::DownloadStatus` each time a download changes.
+#[derive(Serialize, Clone, Debug)]
+pub struct DownloadState {
+    /// Path of the file inside the models folder.
+    pub id: String,
+    pub url: String,
+    pub status: DownloadStatus,
+    pub progress: u64,
+    /// Unknown until the server answers, or when it sends no length.
+    pub total_size: Option<u64>,
+    pub attempt: u32,
+    /// Last error, also kept while waiting for the next attempt.
+    pub error: Option<String>,
+}
+
+#[derive(Clone)]
+struct DownloadRequest {
+    path: PathBuf,
+    url: String,
+    sha256: Option<String>,
+}
+
+struct Job {
+    request: DownloadRequest,
+    state: DownloadState,
+    cancel: Arc<Notify>,
+}
+
+impl Job {
+    fn new(id: &str, request: DownloadRequest) -> Self {
+        Self {
+            state: DownloadState {
+                id: id.to_string(),
+                url: request.url.clone(),
+                status: DownloadStatus::Queued,
+                progress: 0,
+                total_size: None,
+                attempt: 0,
+                error: None,
+            },
+            request,
+            cancel: Arc::new(Notify::new()),
+        }
+    }
+}
+
+/// Why an attempt failed. Network errors are retried, errors on the local disk are not.
+enum Failure {
+    Retry(TranscendiaError),
+    Fatal(TranscendiaError),
+}
+
+impl From<reqwest::Error> for Failure {
+    fn from(err: reqwest::Error) -> Self {
+        let retry = err.status().is_none_or(|s| {
+            s.is_server_error()
+                || s == StatusCode::TOO_MANY_REQUESTS
+                || s == StatusCode::REQUEST_TIMEOUT
+        });
+        if retry {
+            Failure::Retry(err.into())
+        } else {
+            Failure::Fatal(err.into())
+        }
+    }
+}
+
+impl From<std::io::Error> for Failure {
+    fn from(err: std::io::Error) -> Self {
+        Failure::Fatal(err.into())
+    }
+}
+
+/// Model downloads of the whole app, a few at a time, each one retried on network errors.
+/// Completed downloads are forgotten, failed and cancelled ones are kept to be retried.
+#[derive(Clone)]
+pub struct DownloadManager {
+    jobs: Arc<Mutex<HashMap<String, Job>>>,
+    slots: Arc<Semaphore>,
+}
+
+impl Default for DownloadManager {
+    fn default() -> Self {
+        Self {
+            jobs: Arc::default(),
+            slots: Arc::new(Semaphore::new(MAX_PARALLEL_DOWNLOADS)),
+        }
+    }
+}
+
+impl DownloadManager {
+    /// Downloads `url` to `path`, resuming a previous partial download when there is one.
+    /// `path` only exists once the file is complete and matches `sha256` when given.
+    /// Does nothing when the same file is already queued or downloading.
+    pub fn enqueue(
+        &self,
+        app: &AppHandle,
+        path: PathBuf,
+        url: &str,
+        sha256: Option<&str>,
+    ) -> Result<(), TranscendiaError> {
+        let id = download_id(app, &path);
+        let request = DownloadRequest {
+            path,
+            url: url.to_string(),
+            sha256: sha256.map(str::to_string),
+        };
+        {
+            let mut jobs = self.jobs()?;
+            if jobs.get(&id).is_some_and(|j| j.state.status.is_active()) {
+                return Ok(());
+            }
+            jobs.insert(id.clone(), Job::new(&id, request));
+        }
+        self.start(app, id);
+        Ok(())
+    }
+
+    /// Starts a failed or cancelled download again, from where it stopped.
+    pub fn retry(&self, app: &AppHandle, id: &str) -> Result<(), TranscendiaError> {
+        {
+            let mut jobs = self.jobs()?;
+            let job = jobs
+                .get_mut(id)
+                .ok_or_else(|| TranscendiaError::Download(format!("No download of '{}'", id)))?;
+            if job.state.status.is_active() {
+                return Ok(());
+            }
+            *job = Job::new(id, job.request.clone());
+        }
+        self.start(app, id.to_string());
+        Ok(())
+    }
+
+    /// Stops the queued and running downloads, their partial files are kept for a retry.
+    pub fn cancel_all(&self) -> Result<(), TranscendiaError> {
+        for job in self.jobs()?.values() {
+            if job.state.status.is_active() {
+                job.cancel.notify_one();
+            }
+        }
+        Ok(())
+    }
+
+    pub fn states(&self) -> Result<Vec<DownloadState>, TranscendiaError> {
+        let mut states: Vec<DownloadState> =
+            self.jobs()?.values().map(|j| j.state.clone()).collect();
+        states.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(states)
+    }
+
+    fn jobs(&self) -> Result<MutexGuard<'_, HashMap<String, Job>>, TranscendiaError> {
+        lock(&self.jobs, "download")
+    }
+
+    /// Forgets a completed download, then sends `Events::ModelsReady` if the config has all its
+    /// models. Checked after every download that ends, as the one that failed may not be needed.
+    fn finish(&self, app: &AppHandle, id: &str, done: bool) {
+        if done {
+            match self.jobs() {
+                Ok(mut jobs) => {
+                    jobs.remove(id);
+                }
+                Err(err) => report_error(app, "", &err),
+            }
+        }
+        notify_if_models_ready(app);
+    }
+
+    fn start(&self, app: &AppHandle, id: String) {
+        self.update(app, &id, |_| {});
+
+        let manager = self.clone();
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            manager.run(&app, &id).await;
+        });
+    }
+
+    /// Applies `change` to the state of a download and sends the result to the windows.
+    fn update(&self, app: &AppHandle, id: &str, change: impl FnOnce(&mut DownloadState)) {
+        let state = {
+            let mut jobs = match self.jobs() {
+                Ok(jobs) => jobs,
+                Err(err) => return report_error(app, "", &err),
+            };
+            let Some(job) = jobs.get_mut(id) else {
+                return;
+            };
+            change(&mut job.state);
+            job.state.clone()
+        };
+        if let Err(err) = app.emit(Events::DownloadStatus.as_str(), state) {
+            eprintln!("Could not emit download status: {}", err);
+        }
+    }
+
+    async fn run(&self, app: &AppHandle, id: &str) {
+        let job = match self.jobs() {
+            Ok(jobs) => jobs.get(id).map(|j| (j.request.clone(), j.cancel.clone())),
+            Err(err) => return report_error(app, "", &err),
+        };
+        let Some((request, cancel)) = job else {
+            return;
+        };
+        if request.path.exists() {
+            self.update(app, id, |s| s.status = DownloadStatus::Done);
+            self.finish(app, id, true);
+            return;
+        }
+
+        // Dropping the attempt on cancel closes the part file, what was written can be resumed.
+        let result = tokio::select! {
+            result = self.attempts(app, id, &request) => result,
+            _ = cancel.notified() => {
+                eprintln!("Download of '{}' cancelled", request.url);
+                self.update(app, id, |s| s.status = DownloadStatus::Cancelled);
+                self.finish(app, id, false);
+                return;
+            }
+        };
+
+        match result {
+            Ok(()) => {
+                self.update(app, id, |s| {
+                    s.status = DownloadStatus::Done;
+                    s.error = None;
+                });
+                self.finish(app, id, true);
+            }
+            Err(err) => {
+                report_error(app, "", &err);
+                self.update(app, id, |s| {
+                    s.status = DownloadStatus::Failed;
+                    s.error = Some(err.to_string());
+                });
+                self.finish(app, id, false);
+            }
+        }
+    }
+
+    async fn attempts(
+        &self,
+        app: &AppHandle,
+        id: &str,
+        request: &DownloadRequest,
+    ) -> Result<(), TranscendiaError> {
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let failure = {
+                let _slot = self.slots.acquire().await.map_err(|_| {
+                    TranscendiaError::Download("the download queue was closed".to_string())
+                })?;
+                self.update(app, id, |s| {
+                    s.status = DownloadStatus::Downloading;
+                    s.attempt = attempt;
+                });
+                match self.attempt(app, id, request).await {
+                    Ok(()) => return Ok(()),
+                    Err(failure) => failure,
+                }
+            };
+
+            match failure {
+                Failure::Retry(err) if attempt < MAX_ATTEMPTS => {
+                    eprintln!("Download of '{}' failed, retrying: {}", request.url, err);
+                    self.update(app, id, |s| {
+                        s.status = DownloadStatus::Queued;
+                        s.error = Some(err.to_string());
+                    });
+                    sleep(retry_delay(attempt)).await;
+                }
+                Failure::Retry(err) | Failure::Fatal(err) => return Err(err),
+            }
+        }
+    }
+
+    async fn attempt(
+        &self,
+        app: &AppHandle,
+        id: &str,
+        request: &DownloadRequest,
+    ) -> Result<(), Failure> {
+        if request.url.starts_with("file://") {
+            let size = copy_file(&request.path, &request.url).await?;
+            self.update(app, id, |s| {
+                s.progress = size;
+                s.total_size = Some(size);
+            });
+        } else {
+            self.download_file(app, id, request).await?;
+        }
+
+        self.update(app, id, |s| s.status = DownloadStatus::Verifying);
+        // A checksum mismatch removes the part file, so the next attempt starts from scratch.
+        verify_and_install(&request.path, &request.url, request.sha256.as_deref())
+            .await
+            .map_err(Failure::Retry)
+    }
+
+    async fn download_file(
+        &self,
+        app: &AppHandle,
+        id: &str,
+        request: &DownloadRequest,
+    ) -> Result<(), Failure> {
+        let url = &request.url;
+        let client = client(url).map_err(|e| Failure::Fatal(e.into()))?;
+
+        let part = part_path(&request.path);
+        let offset = tokio::fs::metadata(&part)
+            .await
+            .map(|m| m.len())
+            .unwrap_or(0);
+        let mut get = client.get(url);
+        if offset > 0 {
+            get = get.header(RANGE, format!("bytes={}-", offset));
+        }
+        let res = get.send().await?;
+        if res.status() == StatusCode::RANGE_NOT_SATISFIABLE {
+            tokio::fs::remove_file(&part).await?;
+            return Err(Failure::Retry(TranscendiaError::Download(format!(
+                "Partial download of '{}' is invalid, it will restart from scratch",
+                url
+            ))));
+        }
+        let res = res.error_for_status()?;
+
+        // A server that ignores the range sends the whole file again.
+        let offset = if res.status() == StatusCode::PARTIAL_CONTENT {
+            offset
+        } else {
+            0
+        };
+        let total_size = res.content_length().map(|len| offset + len);
+        let mut file = if offset > 0 {
+            OpenOptions::new().append(true).open(&part).await?
+        } else {
+            File::create(&part).await?
+        };
+        let mut downloaded = offset;
+        self.update(app, id, |s| {
+            s.progress = downloaded;
+            s.total_size = total_size;
+        });
+
+        let mut last_event = Instant::now();
+        let mut stream = res.bytes_stream();
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk?;
+            file.write_all(&chunk).await?;
+            downloaded += chunk.len() as u64;
+            if last_event.elapsed() >= PROGRESS_EVENT_INTERVAL {
+                last_event = Instant::now();
+                self.update(app, id, |s| s.progress = downloaded);
+            }
+        }
+        file.flush().await?;
+        self.update(app, id, |s| s.progress = downloaded);
+
+        let written = file.metadata().await?.len();
+        if total_size.is_some_and(|total| written != total) {
+            return Err(Failure::Retry(TranscendiaError::Download(format!(
+                "Download of '{}' ended early",
+                url
+            ))));
+        }
+        eprintln!("Download of '{}' complete", url);
+        Ok(())
+    }
+}
+
+/// Downloads are identified by their path in the models folder, which is also what the UI shows.
+fn download_id(app: &AppHandle, path: &Path) -> String {
+    let models_folder = get_models_folder(app);
+    path.strip_prefix(&models_folder)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+fn client(url: &str) -> reqwest::Result<Client> {
+    Client::builder()
+        .connect_timeout(Duration::from_secs(10))
+        // Plain HTTP only when the URL asks for it, for mirrors inside a local network.
+        .https_only(!url.starts_with("http://"))
+        .read_timeout(Duration::from_secs(20))
+        .build()
+}
+
+/// Small text file next to the downloads, such as the checksums of a translation model.
+pub async fn fetch_text(url: &str) -> Result<String, TranscendiaError> {
+    if url.starts_with("file://") {
+        let path = Url::parse(url)
+            .ok()
+            .and_then(|u| u.to_file_path().ok())
+            .ok_or_else(|| TranscendiaError::Download(format!("Invalid file URL '{}'", url)))?;
+        return Ok(tokio::fs::read_to_string(path).await?);
+    }
+    Ok(client(url)?
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?)
+}
+
+fn retry_delay(attempt: u32) -> Duration {
+    min(
+        RETRY_BASE_DELAY.saturating_mul(2u32.saturating_pow(attempt - 1)),
+        RETRY_MAX_DELAY,
+    )
+}
+
+pub fn part_path(path: &Path) -> PathBuf {
+    let mut part = OsString::from(path.as_os_str());
+    part.push(PART_SUFFIX);
+    PathBuf::from(part)
+}
+
+/// Mirrors on a local or network drive, the file is copied in one step.
+async fn copy_file(path: &Path, url: &str) -> Result<u64, Failure> {
+    let source = Url::parse(url)
+        .ok()
+        .and_then(|u| u.to_file_path().ok())
+        .ok_or_else(|| {
+            Failure::Fatal(TranscendiaError::Download(format!(
+                "Invalid file URL '{}'",
+                url
+            )))
+        })?;
+    Ok(tokio::fs::copy(&source, part_path(path)).await?)
+}
+
+/// Checks a complete part file and moves it to its final path in one rename. Without a
+/// checksum, a `.rten` file must at least load as a model, which catches truncated files.
+pub async fn verify_and_install(
+    path: &Path,
+    source: &str,
+    sha256: Option<&str>,
+) -> Result<(), TranscendiaError> {
+    let part = part_path(path);
+    if let Some(expected) = sha256 {
+        let actual = file_sha256(part.clone()).await?;
+        if !actual.eq_ignore_ascii_case(expected) {
+            tokio::fs::remove_file(&part).await?;
+            return Err(TranscendiaError::Download(format!(
+                "Checksum mismatch for '{}': expected {}, got {}",
+                source, expected, actual
+            )));
+        }
+    } else if path.extension().is_some_and(|ext| ext == "rten") {
+        let model = part.clone();
+        let loaded =
+            tauri::async_runtime::spawn_blocking(move || rten::Model::load_file(model).map(|_| ()))
+                .await?;
+        if let Err(err) = loaded {
+            tokio::fs::remove_file(&part).await?;
+            return Err(TranscendiaError::Download(format!(
+                "'{}' is not a valid model: {}",
+                source, err
+            )));
+        }
+    }
+
+    tokio::fs::rename(&part, path).await?;
+    Ok(())
+}
+
+async fn file_sha256(path: PathBuf) -> Result<String, TranscendiaError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut hasher = Sha256::new();
+        std::io::copy(&mut fs::File::open(path)?, &mut hasher)?;
+        Ok(format!("{:x}", hasher.finalize()))
+    })
+    .await?
+}
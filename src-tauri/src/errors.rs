@@ -1,11 +1,170 @@
 // Copyright © 2025 Nantsa Montillet
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
-use serde::Serialize;
+use crate::events::Events;
+use crate::translators::TranslateError;
+use serde::{Serialize, Serializer};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::sync::{Mutex, MutexGuard};
+use tauri::{AppHandle, Emitter};
+
+/// Source errors of the libraries that only give an opaque error type.
+type BoxError = Box<dyn Error + Send + Sync>;
 
 #[derive(Debug, Serialize)]
+#[serde(tag = "kind", content = "cause")]
 pub enum TranscendiaError {
     CannotLoadConfig,
     CannotSaveConfig,
     CannotGetMonitors,
+    /// A thread panicked while holding the lock on this shared state.
+    LockPoisoned(&'static str),
+    InvalidConfig(String),
+    InvalidProfileName(String),
+    ProfileNotFound(String),
+    ProfileExists(String),
+    MonitorNotFound(u32),
+    WindowNotFound(String),
+    InvalidHotkey(String),
+    ModelNotFound(String),
+    ModelInUse(String),
+    InvalidModelFile(String),
+    InvalidLanguagePair(String),
+    NoCapturePermission,
+    /// The capture or OCR thread of a region exited, after a panic for instance.
+    ThreadStopped(&'static str),
+    Download(String),
+    Capture(#[serde(serialize_with = "as_message")] xcap::XCapError),
+    ModelLoad(#[serde(serialize_with = "as_message")] BoxError),
+    Ocr(#[serde(serialize_with = "as_message")] BoxError),
+    Translation(#[serde(serialize_with = "as_message")] TranslateError),
+    Http(#[serde(serialize_with = "as_message")] reqwest::Error),
+    Clipboard(#[serde(serialize_with = "as_message")] BoxError),
+    Tauri(#[serde(serialize_with = "as_message")] tauri::Error),
+    Io(#[serde(serialize_with = "as_message")] std::io::Error),
+}
+
+/// The frontend only shows the message of a source error.
+fn as_message<S: Serializer>(err: &impl Display, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(err)
+}
+
+impl Display for TranscendiaError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TranscendiaError::CannotLoadConfig => write!(f, "Could not load the configuration"),
+            TranscendiaError::CannotSaveConfig => write!(f, "Could not save the configuration"),
+            TranscendiaError::CannotGetMonitors => write!(f, "Could not list monitors"),
+            TranscendiaError::LockPoisoned(state) => {
+                write!(f, "The {} state is unusable after an internal error", state)
+            }
+            TranscendiaError::InvalidConfig(cause) => write!(f, "Invalid configuration: {}", cause),
+            TranscendiaError::InvalidProfileName(name) => {
+                write!(f, "'{}' is not a valid profile name", name)
+            }
+            TranscendiaError::ProfileNotFound(name) => write!(f, "Profile '{}' not found", name),
+            TranscendiaError::ProfileExists(name) => write!(f, "Profile '{}' already exists", name),
+            TranscendiaError::MonitorNotFound(id) => write!(f, "Monitor {} not found", id),
+            TranscendiaError::WindowNotFound(app) => write!(f, "No open window for {}", app),
+            TranscendiaError::InvalidHotkey(cause) => write!(f, "Invalid hotkey: {}", cause),
+            TranscendiaError::ModelNotFound(id) => write!(f, "Unknown OCR model '{}'", id),
+            TranscendiaError::ModelInUse(id) => {
+                write!(f, "OCR model '{}' is selected and cannot be removed", id)
+            }
+            TranscendiaError::InvalidModelFile(path) => {
+                write!(f, "'{}' is not an .rten model file", path)
+            }
+            TranscendiaError::InvalidLanguagePair(pair) => {
+                write!(f, "'{}' is not a valid language pair", pair)
+            }
+            TranscendiaError::NoCapturePermission => write!(f, "No permission for screen capture"),
+            TranscendiaError::ThreadStopped(thread) => write!(f, "The {} thread stopped", thread),
+            TranscendiaError::Download(cause) => write!(f, "Download failed: {}", cause),
+            TranscendiaError::Capture(err) => write!(f, "Screen capture failed: {}", err),
+            TranscendiaError::ModelLoad(err) => write!(f, "Could not load OCR models: {}", err),
+            TranscendiaError::Ocr(err) => write!(f, "Text recognition failed: {}", err),
+            TranscendiaError::Translation(err) => write!(f, "{}", err),
+            TranscendiaError::Http(err) => write!(f, "HTTP request failed: {}", err),
+            TranscendiaError::Clipboard(err) => write!(f, "Clipboard error: {}", err),
+            TranscendiaError::Tauri(err) => write!(f, "Window error: {}", err),
+            TranscendiaError::Io(err) => write!(f, "I/O error: {}", err),
+        }
+    }
+}
+
+impl Error for TranscendiaError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TranscendiaError::Capture(err) => Some(err),
+            TranscendiaError::ModelLoad(err)
+            | TranscendiaError::Ocr(err)
+            | TranscendiaError::Clipboard(err) => Some(err.as_ref()),
+            TranscendiaError::Translation(err) => Some(err),
+            TranscendiaError::Http(err) => Some(err),
+            TranscendiaError::Tauri(err) => Some(err),
+            TranscendiaError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<xcap::XCapError> for TranscendiaError {
+    fn from(err: xcap::XCapError) -> Self {
+        TranscendiaError::Capture(err)
+    }
+}
+
+impl From<TranslateError> for TranscendiaError {
+    fn from(err: TranslateError) -> Self {
+        TranscendiaError::Translation(err)
+    }
+}
+
+impl From<tauri::Error> for TranscendiaError {
+    fn from(err: tauri::Error) -> Self {
+        TranscendiaError::Tauri(err)
+    }
+}
+
+impl From<std::io::Error> for TranscendiaError {
+    fn from(err: std::io::Error) -> Self {
+        TranscendiaError::Io(err)
+    }
+}
+
+impl From<reqwest::Error> for TranscendiaError {
+    fn from(err: reqwest::Error) -> Self {
+        TranscendiaError::Http(err)
+    }
+}
+
+/// Locks state shared between threads. A thread that panicked while holding the lock may have
+/// left it half updated, so it is reported as `LockPoisoned` instead of being used.
+pub fn lock<'a, T>(
+    mutex: &'a Mutex<T>,
+    state: &'static str,
+) -> Result<MutexGuard<'a, T>, TranscendiaError> {
+    mutex
+        .lock()
+        .map_err(|_| TranscendiaError::LockPoisoned(state))
+}
+
+/// Payload of `Events::RuntimeError`, `region` is empty for errors outside a capture worker.
+#[derive(Serialize, Clone)]
+pub struct RuntimeError {
+    pub region: String,
+    pub message: String,
+}
+
+/// Logs the error and forwards it to the overlay and config windows.
+pub fn report_error(app: &AppHandle, region: &str, err: &TranscendiaError) {
+    eprintln!("[{}] {}", region, err);
+    let payload = RuntimeError {
+        region: region.to_string(),
+        message: err.to_string(),
+    };
+    if let Err(err) = app.emit(Events::RuntimeError.as_str(), payload) {
+        eprintln!("Could not emit runtime error: {}", err);
+    }
 }
@@ -5,8 +5,12 @@ pub enum Events {
     OnOffConfigTrayItem,
     RefreshOverlay,
     NewTranslatedText,
-    DownloadProgress,
-    StopDownload,
+    DownloadStatus,
+    ModelsReady,
+    RuntimeError,
+    ProfilesChanged,
+    RuntimeModeChanged,
+    RuntimeStats,
 }
 
 impl Events {
@@ -15,8 +19,12 @@ impl Events {
             Events::OnOffConfigTrayItem => "OnOffConfigTrayItem",
             Events::RefreshOverlay => "RefreshOverlay",
             Events::NewTranslatedText => "NewTranslatedText",
-            Events::DownloadProgress => "DownloadProgress",
-            Events::StopDownload => "StopDownload",
+            Events::DownloadStatus => "DownloadStatus",
+            Events::ModelsReady => "ModelsReady",
+            Events::RuntimeError => "RuntimeError",
+            Events::ProfilesChanged => "ProfilesChanged",
+            Events::RuntimeModeChanged => "RuntimeModeChanged",
+            Events::RuntimeStats => "RuntimeStats",
         }
     }
 }
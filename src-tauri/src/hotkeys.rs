@@ -0,0 +1,155 @@
+// Copyright © 2025 Nantsa Montillet
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::commands::{
+    begin_region_selection, change_runtime_mode, switch_profile, SelectionState,
+};
+use crate::config::{ConfigState, Hotkeys};
+use crate::errors::{lock, report_error, TranscendiaError};
+use crate::profiles::{list_profiles, ProfilesState};
+use crate::translate_runtime::{RuntimeMode, TranslateRuntime};
+use crate::windows::toggle_overlays;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tauri_plugin_global_shortcut::{Code, GlobalShortcutExt, Modifiers, Shortcut, ShortcutState};
+
+/// Shortcuts registered from `Config::hotkeys`, replaced as a whole on every change.
+#[derive(Default)]
+pub struct HotkeysState(pub Mutex<Vec<Shortcut>>);
+
+#[derive(Clone, Copy, Debug)]
+enum HotkeyAction {
+    PauseResume,
+    ReselectRegion,
+    ToggleOverlay,
+    Retranslate,
+    CopyTranslation,
+    CycleProfile,
+}
+
+/// Closes the select window, always registered and never configurable.
+pub fn select_shortcut() -> Shortcut {
+    Shortcut::new(Some(Modifiers::CONTROL), Code::KeyX)
+}
+
+fn bindings(hotkeys: &Hotkeys) -> [(HotkeyAction, &Option<String>); 6] {
+    [
+        (HotkeyAction::PauseResume, &hotkeys.pause_resume),
+        (HotkeyAction::ReselectRegion, &hotkeys.reselect_region),
+        (HotkeyAction::ToggleOverlay, &hotkeys.toggle_overlay),
+        (HotkeyAction::Retranslate, &hotkeys.retranslate),
+        (HotkeyAction::CopyTranslation, &hotkeys.copy_translation),
+        (HotkeyAction::CycleProfile, &hotkeys.cycle_profile),
+    ]
+}
+
+pub fn register_hotkeys(app: &AppHandle, hotkeys: &Hotkeys) {
+    let global_shortcut = app.global_shortcut();
+    let state = app.state::<HotkeysState>();
+    let mut registered = match lock(&state.0, "hotkeys") {
+        Ok(registered) => registered,
+        Err(err) => {
+            report_error(app, "", &err);
+            return;
+        }
+    };
+
+    for shortcut in registered.drain(..) {
+        if let Err(err) = global_shortcut.unregister(shortcut) {
+            eprintln!("Failed to unregister {} : {}", shortcut, err);
+        }
+    }
+
+    for (action, key) in bindings(hotkeys) {
+        let Some(key) = key.as_deref().map(str::trim).filter(|k| !k.is_empty()) else {
+            continue;
+        };
+        let shortcut = match key.parse::<Shortcut>() {
+            Ok(shortcut) => shortcut,
+            Err(err) => {
+                let err = TranscendiaError::InvalidHotkey(format!("{} ({})", key, err));
+                report_error(app, "", &err);
+                continue;
+            }
+        };
+        if shortcut == select_shortcut() || registered.contains(&shortcut) {
+            let err = TranscendiaError::InvalidHotkey(format!("{} is already used", key));
+            report_error(app, "", &err);
+            continue;
+        }
+
+        // The plugin runs handlers under its own lock, actions that re-register would deadlock.
+        let result = global_shortcut.on_shortcut(shortcut, move |app, _, event| {
+            if event.state == ShortcutState::Pressed {
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(err) = run_action(&app, action) {
+                        report_error(&app, "", &err);
+                    }
+                });
+            }
+        });
+        match result {
+            Ok(()) => registered.push(shortcut),
+            Err(err) => {
+                let err = TranscendiaError::InvalidHotkey(format!("{} ({})", key, err));
+                report_error(app, "", &err);
+            }
+        }
+    }
+}
+
+fn run_action(app: &AppHandle, action: HotkeyAction) -> Result<(), TranscendiaError> {
+    let runtime = app.state::<TranslateRuntime>();
+    match action {
+        HotkeyAction::PauseResume => {
+            let mode = match runtime.mode()? {
+                RuntimeMode::Running => RuntimeMode::Paused,
+                RuntimeMode::Paused | RuntimeMode::OneShot => RuntimeMode::Running,
+            };
+            change_runtime_mode(app, mode)?;
+        }
+        HotkeyAction::ReselectRegion => {
+            let last_edited = {
+                let selection = app.state::<SelectionState>();
+                let selection = lock(&selection.0, "region selection")?;
+                selection.last_edited.clone()
+            };
+            let region_id = {
+                let config = app.state::<ConfigState>();
+                let config = lock(&config.0, "config")?;
+                last_edited
+                    .filter(|id| config.find_region(id).is_some())
+                    .or_else(|| config.regions.first().map(|r| r.id.clone()))
+            };
+            if let Some(region_id) = region_id {
+                if let Some(w) = app.get_webview_window("config") {
+                    w.close()?;
+                }
+                begin_region_selection(app, region_id)?;
+            }
+        }
+        HotkeyAction::ToggleOverlay => toggle_overlays(app)?,
+        HotkeyAction::Retranslate => runtime.retranslate()?,
+        HotkeyAction::CopyTranslation => {
+            if let Some(text) = runtime.last_translation()? {
+                app.clipboard()
+                    .write_text(text)
+                    .map_err(|e| TranscendiaError::Clipboard(e.into()))?;
+            }
+        }
+        HotkeyAction::CycleProfile => {
+            let profiles = list_profiles(app);
+            let active = app.state::<ProfilesState>().active()?;
+            let next = active
+                .and_then(|a| profiles.iter().position(|p| *p == a))
+                .map_or(0, |i| (i + 1) % profiles.len().max(1));
+            if let Some(profile) = profiles.get(next) {
+                switch_profile(app, profile)?;
+            }
+        }
+    }
+
+    Ok(())
+}
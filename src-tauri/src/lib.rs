@@ -1,33 +1,51 @@
 // Copyright © 2025 Nantsa Montillet
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
+mod capture;
 mod commands;
 mod config;
+mod downloads;
 mod errors;
 mod events;
+mod hotkeys;
+mod model_manifest;
+mod ocr;
 mod ocr_models;
+mod profiles;
+mod runtime_stats;
+mod similarity;
 mod systray;
 mod translate_runtime;
+mod translation_cache;
+mod translators;
 mod windows;
 
 use crate::commands::{
-    download_finish, f_s_r, finish_select_region, get_config, get_monitors, select_region,
-    set_config,
+    activate_profile, cancel_downloads, clone_profile, create_profile, delete_ocr_model,
+    delete_profile, download_ocr_model, f_s_r, finish_select_region, get_config, get_monitors,
+    get_runtime_mode, get_runtime_stats, get_windows, import_ocr_model, list_downloads,
+    list_models, list_profiles, models_ready, retry_download, select_region, set_auto_switch,
+    set_config, set_profile_bindings, set_region_window, set_runtime_mode, translate_once,
+    SelectionState,
 };
 use crate::config::{Config, ConfigState};
-use crate::ocr_models::check_for_models;
+use crate::downloads::DownloadManager;
+use crate::errors::report_error;
+use crate::events::Events;
+use crate::hotkeys::{register_hotkeys, select_shortcut, HotkeysState};
+use crate::ocr_models::{check_for_models, seed_models};
+use crate::profiles::{start_profile_watcher, ProfilesState};
+use crate::runtime_stats::start_stats_emitter;
 use crate::systray::create_systray;
-use crate::translate_runtime::{start_translate_runtime, TranslateRuntime};
-use crate::windows::{create_config_window, create_overlay_window};
-use std::sync::atomic::{AtomicBool, AtomicU8};
-use std::sync::{Arc, Mutex};
-use tauri::{generate_context, generate_handler, ActivationPolicy, Manager};
-use tauri_plugin_global_shortcut::{Code, Modifiers, Shortcut, ShortcutState};
-use tokio::sync::Notify;
+use crate::translate_runtime::TranslateRuntime;
+use crate::translation_cache::{start_cache_flusher, TranslationCache};
+use std::sync::Mutex;
+use tauri::{generate_context, generate_handler, ActivationPolicy, Listener, Manager, RunEvent};
+use tauri_plugin_global_shortcut::ShortcutState;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    let close_shortcut = Shortcut::new(Some(Modifiers::CONTROL), Code::KeyX);
+    let close_shortcut = select_shortcut();
 
     tauri::Builder::default()
         .plugin(
@@ -39,9 +57,12 @@ pub fn run() {
                         if let Some(w) = window {
                             let state = _app.state::<ConfigState>();
                             let runtime = _app.state::<TranslateRuntime>();
-                            f_s_r(_app.clone(), state, runtime, true)
-                                .expect("Failed reopen windows");
-                            w.close().expect("Failed to close window");
+                            if let Err(err) = f_s_r(_app.clone(), state, runtime, true) {
+                                report_error(_app, "", &err);
+                            }
+                            if let Err(err) = w.close() {
+                                eprintln!("Failed to close select window: {}", err);
+                            }
                         }
                     }
                 })
@@ -49,6 +70,7 @@ pub fn run() {
                 .expect("Shortcut error")
                 .build(),
         )
+        .plugin(tauri_plugin_clipboard_manager::init())
         .plugin(tauri_plugin_macos_permissions::init())
         .plugin(tauri_plugin_process::init())
         .plugin(tauri_plugin_shell::init())
@@ -56,35 +78,39 @@ pub fn run() {
             #[cfg(target_os = "macos")]
             app.set_activation_policy(ActivationPolicy::Accessory);
 
-            create_systray(app)?;
-
             let app = app.handle();
 
             let config = Config::load(app);
             app.manage(ConfigState(Mutex::new(config.clone())));
+            app.manage(TranslateRuntime::new(TranslationCache::load(app)));
+            app.manage(SelectionState::default());
+            app.manage(ProfilesState::load(app));
+            app.manage(HotkeysState::default());
+            app.manage(DownloadManager::default());
 
-            let runtime = TranslateRuntime {
-                need_stop: Arc::new(Notify::default()),
-                is_running: Arc::new(AtomicBool::from(false)),
-                interval: Arc::new(AtomicU8::new(config.interval)),
-            };
+            create_systray(app)?;
+            start_profile_watcher(app);
+            start_stats_emitter(app);
+            start_cache_flusher(app);
+            register_hotkeys(app, &config.hotkeys);
 
-            if check_for_models(app) {
-                if let Some(region) = config.region {
-                    start_translate_runtime(
-                        app,
-                        &runtime,
-                        config.monitor.clone(),
-                        region.clone(),
-                        config.lang.clone(),
-                    );
-                    create_overlay_window(app, &region, config.monitor, config.blur_background)?;
-                } else {
-                    create_config_window(app)?;
+            let handle = app.clone();
+            app.listen(Events::ModelsReady.as_str(), move |_| {
+                if let Err(err) = models_ready(&handle) {
+                    report_error(&handle, "", &err);
                 }
-            }
+            });
 
-            app.manage(runtime);
+            seed_models(app);
+            if check_for_models(app, &config) {
+                let has_region = config.regions.iter().any(|r| r.region.is_some());
+                f_s_r(
+                    app.clone(),
+                    app.state::<ConfigState>(),
+                    app.state::<TranslateRuntime>(),
+                    !has_region,
+                )?;
+            }
 
             Ok(())
         })
@@ -92,10 +118,34 @@ pub fn run() {
             get_config,
             set_config,
             get_monitors,
+            get_windows,
+            set_region_window,
             select_region,
             finish_select_region,
-            download_finish
+            list_downloads,
+            cancel_downloads,
+            retry_download,
+            list_profiles,
+            create_profile,
+            clone_profile,
+            delete_profile,
+            activate_profile,
+            set_profile_bindings,
+            set_auto_switch,
+            get_runtime_mode,
+            set_runtime_mode,
+            translate_once,
+            get_runtime_stats,
+            list_models,
+            download_ocr_model,
+            import_ocr_model,
+            delete_ocr_model
         ])
-        .run(generate_context!())
-        .expect("Error while running Transcendia");
+        .build(generate_context!())
+        .expect("Error while building Transcendia")
+        .run(|app, event| {
+            if let RunEvent::Exit = event {
+                TranslationCache::flush(&app.state::<TranslateRuntime>().cache());
+            }
+        });
 }
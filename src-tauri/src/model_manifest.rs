@@ -0,0 +1,123 @@
+// Copyright © 2025 Nantsa Montillet
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::downloads::part_path;
+use crate::errors::TranscendiaError;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Models shipped with the app, see `models.json` at the root of the crate.
+const BUILTIN_MANIFEST: &str = include_str!("../models.json");
+/// Extra entries in the same format, read from the models folder. An entry with the id of a
+/// built-in model replaces it, which is how alternative recognisers are added.
+const USER_MANIFEST_FILE: &str = "manifest.json";
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ModelKind {
+    Detection,
+    Recognition,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ModelEntry {
+    pub id: String,
+    pub name: String,
+    pub kind: ModelKind,
+    pub version: String,
+    pub url: String,
+    /// File name inside the models folder.
+    pub file: String,
+    /// Size in bytes, shown before the download starts.
+    pub size: Option<u64>,
+    /// Without it, the file is only checked to load as a model, see `verify_and_install`.
+    pub sha256: Option<String>,
+    /// Scripts the model reads, empty when it does not depend on the script.
+    #[serde(default)]
+    pub scripts: Vec<String>,
+}
+
+impl ModelEntry {
+    pub fn path(&self, models_folder: &Path) -> PathBuf {
+        models_folder.join(&self.file)
+    }
+
+    pub fn is_installed(&self, models_folder: &Path) -> bool {
+        self.path(models_folder).exists()
+    }
+
+    /// Entries from the user manifest end up as file names, anything else is rejected.
+    fn has_valid_file(&self) -> bool {
+        Path::new(&self.file)
+            .file_name()
+            .is_some_and(|f| f == self.file.as_str())
+    }
+}
+
+/// Built-in models followed by the ones from the user manifest.
+pub fn load_manifest(models_folder: &Path) -> Vec<ModelEntry> {
+    let mut entries: Vec<ModelEntry> =
+        serde_json::from_str(BUILTIN_MANIFEST).expect("Invalid built-in model manifest");
+
+    let user_manifest = models_folder.join(USER_MANIFEST_FILE);
+    let extra = match fs::read_to_string(&user_manifest) {
+        Ok(data) => serde_json::from_str::<Vec<ModelEntry>>(&data).unwrap_or_else(|err| {
+            eprintln!("Invalid {}: {}", USER_MANIFEST_FILE, err);
+            Vec::new()
+        }),
+        Err(_) => Vec::new(),
+    };
+    for entry in extra.into_iter().filter(ModelEntry::has_valid_file) {
+        entries.retain(|e| e.id != entry.id);
+        entries.push(entry);
+    }
+
+    entries
+}
+
+#[derive(Serialize)]
+pub struct ModelStatus {
+    #[serde(flatten)]
+    pub entry: ModelEntry,
+    pub installed: bool,
+}
+
+pub fn list_models(models_folder: &Path) -> Vec<ModelStatus> {
+    load_manifest(models_folder)
+        .into_iter()
+        .map(|entry| ModelStatus {
+            installed: entry.is_installed(models_folder),
+            entry,
+        })
+        .collect()
+}
+
+pub fn find_any_model(models_folder: &Path, id: &str) -> Result<ModelEntry, TranscendiaError> {
+    load_manifest(models_folder)
+        .into_iter()
+        .find(|e| e.id == id)
+        .ok_or_else(|| TranscendiaError::ModelNotFound(id.to_string()))
+}
+
+pub fn find_model(
+    models_folder: &Path,
+    id: &str,
+    kind: ModelKind,
+) -> Result<ModelEntry, TranscendiaError> {
+    load_manifest(models_folder)
+        .into_iter()
+        .find(|e| e.id == id && e.kind == kind)
+        .ok_or_else(|| TranscendiaError::ModelNotFound(id.to_string()))
+}
+
+/// Removes the model file and any partial download of it.
+pub fn delete_model(models_folder: &Path, entry: &ModelEntry) -> Result<(), TranscendiaError> {
+    let path = entry.path(models_folder);
+    for file in [part_path(&path), path] {
+        if file.exists() {
+            fs::remove_file(file)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,146 @@
+// Copyright © 2025 Nantsa Montillet
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::capture::Frame;
+use crate::errors::TranscendiaError;
+use crate::translate_runtime::TextBlock;
+use ocrs::{ImageSource, OcrEngine, OcrEngineParams, TextItem};
+use rten::Model;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::{channel, Receiver, Sender};
+
+/// Frames waiting for recognition, the OCR stage sends one and waits for its result.
+const OCR_QUEUE_SIZE: usize = 1;
+
+type OcrResult = Result<Recognition, TranscendiaError>;
+
+pub struct Recognition {
+    pub blocks: Vec<TextBlock>,
+    pub detect_time: Duration,
+    pub recognize_time: Duration,
+    /// Share of the detected lines that produced text, see `RegionStats::recognized_line_ratio`.
+    pub recognized_line_ratio: Option<f32>,
+}
+
+/// OCR engine owned by a dedicated thread, so detection and recognition never run on the
+/// async runtime. The thread stops when this handle is dropped.
+pub struct OcrWorker {
+    frames: Sender<Arc<Frame>>,
+    results: Receiver<OcrResult>,
+    thread: JoinHandle<()>,
+}
+
+impl OcrWorker {
+    pub fn spawn(
+        region_id: &str,
+        detection_model: PathBuf,
+        recognition_model: PathBuf,
+    ) -> Result<Self, TranscendiaError> {
+        let (frames, mut frame_rx) = channel::<Arc<Frame>>(OCR_QUEUE_SIZE);
+        let (result_tx, results) = channel::<OcrResult>(OCR_QUEUE_SIZE);
+
+        let thread = std::thread::Builder::new()
+            .name(format!("ocr-{}", region_id))
+            .spawn(move || {
+                // Loaded on the first frame and kept on failure as `None`, so it is retried.
+                let mut engine: Option<OcrEngine> = None;
+                while let Some(frame) = frame_rx.blocking_recv() {
+                    let result = match engine {
+                        Some(ref engine) => process_screenshot(&frame, engine),
+                        None => load_engine(&detection_model, &recognition_model)
+                            .and_then(|loaded| process_screenshot(&frame, engine.insert(loaded))),
+                    };
+                    if result_tx.blocking_send(result).is_err() {
+                        break;
+                    }
+                }
+            })?;
+
+        Ok(Self {
+            frames,
+            results,
+            thread,
+        })
+    }
+
+    /// False once the thread exited, after a panic in the engine for instance.
+    pub fn is_alive(&self) -> bool {
+        !self.thread.is_finished()
+    }
+
+    pub async fn recognize(&mut self, frame: Arc<Frame>) -> OcrResult {
+        self.frames.send(frame).await.map_err(|_| ocr_stopped())?;
+        self.results.recv().await.ok_or_else(ocr_stopped)?
+    }
+}
+
+fn ocr_stopped() -> TranscendiaError {
+    TranscendiaError::ThreadStopped("OCR")
+}
+
+fn load_engine(
+    detection_model: &Path,
+    recognition_model: &Path,
+) -> Result<OcrEngine, TranscendiaError> {
+    let detection_model =
+        Model::load_file(detection_model).map_err(|e| TranscendiaError::ModelLoad(e.into()))?;
+    let recognition_model =
+        Model::load_file(recognition_model).map_err(|e| TranscendiaError::ModelLoad(e.into()))?;
+
+    OcrEngine::new(OcrEngineParams {
+        detection_model: Some(detection_model),
+        recognition_model: Some(recognition_model),
+        ..Default::default()
+    })
+    .map_err(|e| TranscendiaError::ModelLoad(e.into()))
+}
+
+fn process_screenshot(frame: &Frame, engine: &OcrEngine) -> OcrResult {
+    let sf = frame.scale_factor;
+    let img_source = ImageSource::from_bytes(frame.image.as_raw(), frame.image.dimensions())
+        .map_err(ocr_error)?;
+    let ocr_input = engine.prepare_input(img_source).map_err(ocr_error)?;
+
+    let started = Instant::now();
+    let world_rects = engine.detect_words(&ocr_input).map_err(ocr_error)?;
+    let line_rects = engine.find_text_lines(&ocr_input, &world_rects);
+    let detect_time = started.elapsed();
+
+    let started = Instant::now();
+    let line_texts = engine
+        .recognize_text(&ocr_input, &line_rects)
+        .map_err(ocr_error)?;
+    let recognize_time = started.elapsed();
+
+    let blocks: Vec<TextBlock> = line_texts
+        .iter()
+        .flatten()
+        .filter(|l| l.to_string().len() > 1)
+        .map(|line| {
+            let rect = line.bounding_rect();
+            TextBlock {
+                text: line.to_string(),
+                x: rect.left() as f32 / sf,
+                y: rect.top() as f32 / sf,
+                w: rect.width() as f32 / sf,
+                h: rect.height() as f32 / sf,
+            }
+        })
+        .collect();
+    let recognized_line_ratio =
+        (!line_rects.is_empty()).then(|| blocks.len() as f32 / line_rects.len() as f32);
+
+    Ok(Recognition {
+        blocks,
+        detect_time,
+        recognize_time,
+        recognized_line_ratio,
+    })
+}
+
+fn ocr_error(err: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> TranscendiaError {
+    TranscendiaError::Ocr(err.into())
+}
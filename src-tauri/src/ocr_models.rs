@@ -1,131 +1,388 @@
 // Copyright © 2025 Nantsa Montillet
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
+use crate::config::{Config, ConfigState, OcrModels, TranslatorConfig};
+use crate::downloads::{fetch_text, part_path, verify_and_install, DownloadManager, PART_SUFFIX};
+use crate::errors::{lock, report_error, TranscendiaError};
 use crate::events::Events;
+use crate::model_manifest::{find_model, load_manifest, ModelEntry, ModelKind};
+use crate::translators::{
+    DECODER_MODEL_FILE, ENCODER_MODEL_FILE, MODEL_CONFIG_FILE, TOKENIZER_FILE,
+};
 use crate::windows::create_download_window;
-use futures_util::StreamExt;
-use reqwest::Client;
-use serde::{Deserialize, Serialize};
-use std::cmp::min;
 use std::fs;
-use std::fs::remove_file;
-use std::path::PathBuf;
-use std::sync::Arc;
-use std::time::Duration;
-use tauri::{AppHandle, Emitter, Listener, Manager};
-use tokio::fs::File;
-use tokio::io::AsyncWriteExt;
-use tokio::sync::Notify;
-
-#[derive(Serialize, Deserialize, Clone, Debug)]
-struct DownloadData {
-    file: String,
-    progress: u64,
-    total_size: u64,
-}
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, Manager};
 
 pub const MODEL_FOLDER_NAME: &str = "models";
-pub const DETECTION_MODEL_NAME: &str = "text-detection.rten";
-const DETECTION_MODEL_URL: &str =
-    "https://ocrs-models.s3-accelerate.amazonaws.com/text-detection.rten";
-pub const RECOGNITION_MODEL_NAME: &str = "text-recognition.rten";
-const RECOGNITION_MODEL_URL: &str =
-    "https://ocrs-models.s3-accelerate.amazonaws.com/text-recognition.rten";
-
-pub fn check_for_models(app: &AppHandle) -> bool {
-    let conf_path = app
-        .path()
+pub const TRANSLATION_MODEL_FOLDER_NAME: &str = "translation";
+/// Extension of the OCR model files accepted by the import.
+const OCR_MODEL_EXTENSION: &str = "rten";
+/// Files of a translation model, as written by `scripts/convert-opus-mt.sh`.
+const TRANSLATION_MODEL_FILES: [&str; 4] = [
+    ENCODER_MODEL_FILE,
+    DECODER_MODEL_FILE,
+    TOKENIZER_FILE,
+    MODEL_CONFIG_FILE,
+];
+/// Checksums of the translation model files, in the `sha256sum` format. A mirror serves it
+/// next to them.
+const TRANSLATION_MODEL_CHECKSUMS: &str = "SHA256SUMS";
+/// Folder copied into the models folder at startup, for machines without internet access.
+const SEED_MODELS_ARG: &str = "--seed-models";
+const SEED_MODELS_ENV: &str = "TRANSCENDIA_SEED_MODELS";
+
+pub fn get_models_folder(app: &AppHandle) -> PathBuf {
+    app.path()
         .app_config_dir()
         .expect("Could not get app config dir")
-        .join(MODEL_FOLDER_NAME);
-    if !conf_path.exists() {
-        println!("{:?}", conf_path);
-        fs::create_dir_all(&conf_path).expect("Could not create model dir");
+        .join(MODEL_FOLDER_NAME)
+}
+
+/// The pair ends up in a path, so only names like `en-fr` or `en-ROMANCE` are accepted.
+pub fn translation_model_dir(
+    models_folder: &Path,
+    pair: &str,
+) -> Result<PathBuf, TranscendiaError> {
+    let valid = !pair.is_empty()
+        && !pair.starts_with('-')
+        && pair
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(TranscendiaError::InvalidLanguagePair(pair.to_string()));
     }
 
-    let detection_model_path = conf_path.join(DETECTION_MODEL_NAME);
-    let recognition_model_path = conf_path.join(RECOGNITION_MODEL_NAME);
+    Ok(models_folder
+        .join(TRANSLATION_MODEL_FOLDER_NAME)
+        .join(format!("opus-mt-{}", pair)))
+}
 
-    let mut its_ok = true;
-    if !detection_model_path.exists() {
-        its_ok = false;
-        download_model(app, detection_model_path, DETECTION_MODEL_URL);
+pub fn check_for_models(app: &AppHandle, config: &Config) -> bool {
+    let conf_path = get_models_folder(app);
+    if let Err(err) = fs::create_dir_all(&conf_path) {
+        report_error(app, "", &err.into());
     }
-    if !recognition_model_path.exists() {
+
+    let mirror = config.model_mirror.as_deref();
+    let mut its_ok = download_ocr_models(app, &conf_path, &config.ocr_models, mirror);
+    if !download_translation_model(app, &conf_path, &config.translator, mirror) {
         its_ok = false;
-        download_model(app, recognition_model_path, RECOGNITION_MODEL_URL);
     }
 
     if !its_ok {
-        create_download_window(app).expect("Cannot create download window");
+        if let Err(err) = create_download_window(app) {
+            report_error(app, "", &err);
+        }
+    }
+
+    its_ok
+}
+
+pub fn check_for_ocr_models(app: &AppHandle, config: &Config) -> bool {
+    let its_ok = download_ocr_models(
+        app,
+        &get_models_folder(app),
+        &config.ocr_models,
+        config.model_mirror.as_deref(),
+    );
+    if !its_ok {
+        if let Err(err) = create_download_window(app) {
+            report_error(app, "", &err);
+        }
+    }
+
+    its_ok
+}
+
+pub fn check_for_translation_model(app: &AppHandle, config: &Config) -> bool {
+    let its_ok = download_translation_model(
+        app,
+        &get_models_folder(app),
+        &config.translator,
+        config.model_mirror.as_deref(),
+    );
+    if !its_ok {
+        if let Err(err) = create_download_window(app) {
+            report_error(app, "", &err);
+        }
     }
 
     its_ok
 }
 
-pub fn download_model(app: &AppHandle, path: PathBuf, url: &str) {
-    let stop_notify = Arc::new(Notify::default());
-    let stop_notify_clone = stop_notify.clone();
-    let path_clone = path.clone();
-    let id = app.listen(Events::StopDownload.as_str(), move |_| {
-        let _ = remove_file(&path_clone);
-        stop_notify.notify_one();
+/// True when every model needed by `config` is installed. Downloads only reach their final
+/// path once complete and verified, so a file in progress is never counted.
+pub fn required_models_present(models_folder: &Path, config: &Config) -> bool {
+    let ocr = [
+        (&config.ocr_models.detection, ModelKind::Detection),
+        (&config.ocr_models.recognition, ModelKind::Recognition),
+    ]
+    .into_iter()
+    .all(|(id, kind)| {
+        find_model(models_folder, id, kind).is_ok_and(|e| e.is_installed(models_folder))
     });
 
-    let app = app.clone();
-    let url = url.to_string().clone();
-    tauri::async_runtime::spawn(async move {
-        let client = Client::builder()
-            .connect_timeout(Duration::from_secs(10))
-            .https_only(true)
-            .timeout(Duration::from_secs(20))
-            .build()
-            .expect("Cannot create https client!");
-
-        let res = client
-            .get(url.clone())
-            .send()
-            .await
-            .expect(format!("Failed to GET from '{}'", url.clone()).as_str());
-
-        let total_size = res.content_length().expect("Failed to get content length");
-        let mut file = File::create(&path)
-            .await
-            .expect("Failed to create the file!");
-        let mut downloaded: u64 = 0;
-        let mut stream = res.bytes_stream();
-
-        loop {
-            tokio::select! {
-                _ = stop_notify_clone.notified() => {
-                    println!("Stop download and quit...");
-                    app.exit(0);
-                }
-                data = stream.next() => {
-                    match data {
-                        Some(Ok(chunk)) => {
-                            file.write_all(&chunk).await.expect("Error while writing file");
-                            downloaded = min(downloaded + (chunk.len() as u64), total_size);
-                            println!("Downloaded: {} / {}", downloaded, total_size);
-                            app.emit(Events::DownloadProgress.as_str(), DownloadData {
-                                file: url.clone(),
-                                progress: downloaded.clone(),
-                                total_size: total_size.clone(),
-                            }).expect("Can't emit events");
-                        }
-                        Some(Err(err)) => {
-                            eprintln!("Error while downloading: {}", err);
-                            break;
-                        }
-                        None => {
-                            println!("Download complete!");
-                            break;
-                        }
-                    }
+    let translation = match &config.translator {
+        TranslatorConfig::Offline { model } => translation_model_dir(models_folder, model)
+            .is_ok_and(|dir| {
+                TRANSLATION_MODEL_FILES
+                    .iter()
+                    .all(|file| dir.join(file).exists())
+            }),
+        _ => true,
+    };
+
+    ocr && translation
+}
+
+/// Emits `Events::ModelsReady` when every model needed by the config is installed, which starts
+/// the regions waiting for them.
+pub fn notify_if_models_ready(app: &AppHandle) {
+    let ready = match lock(&app.state::<ConfigState>().0, "config") {
+        Ok(config) => required_models_present(&get_models_folder(app), &config),
+        Err(err) => {
+            report_error(app, "", &err);
+            return;
+        }
+    };
+    if !ready {
+        return;
+    }
+    if let Err(err) = app.emit(Events::ModelsReady.as_str(), None::<bool>) {
+        eprintln!("Could not emit models ready: {}", err);
+    }
+}
+
+/// Starts the download of the selected detection and recognition models that are missing.
+fn download_ocr_models(
+    app: &AppHandle,
+    models_folder: &Path,
+    ocr_models: &OcrModels,
+    mirror: Option<&str>,
+) -> bool {
+    let mut its_ok = true;
+    for (id, kind) in [
+        (&ocr_models.detection, ModelKind::Detection),
+        (&ocr_models.recognition, ModelKind::Recognition),
+    ] {
+        match find_model(models_folder, id, kind) {
+            Ok(entry) if !entry.is_installed(models_folder) => {
+                its_ok = false;
+                if let Err(err) = install_model(app, models_folder, &entry, mirror) {
+                    report_error(app, "", &err);
                 }
             }
+            Ok(_) => {}
+            Err(err) => report_error(app, "", &err),
+        }
+    }
+
+    its_ok
+}
+
+pub fn install_model(
+    app: &AppHandle,
+    models_folder: &Path,
+    entry: &ModelEntry,
+    mirror: Option<&str>,
+) -> Result<(), TranscendiaError> {
+    let url = model_url(mirror, &entry.file, || entry.url.clone());
+    app.state::<DownloadManager>().enqueue(
+        app,
+        entry.path(models_folder),
+        &url,
+        entry.sha256.as_deref(),
+    )
+}
+
+/// A mirror has the same layout as the models folder, so the models folder of another machine
+/// can be served as is, or used directly with a `file://` URL.
+fn model_url(
+    mirror: Option<&str>,
+    relative_path: &str,
+    default: impl FnOnce() -> String,
+) -> String {
+    match mirror.map(str::trim).filter(|m| !m.is_empty()) {
+        Some(mirror) => format!("{}/{}", mirror.trim_end_matches('/'), relative_path),
+        None => default(),
+    }
+}
+
+/// Copies a model file picked by the user in place of downloading it.
+pub async fn import_model(
+    models_folder: &Path,
+    entry: &ModelEntry,
+    source: &Path,
+) -> Result<(), TranscendiaError> {
+    if source.extension().and_then(|ext| ext.to_str()) != Some(OCR_MODEL_EXTENSION) {
+        return Err(TranscendiaError::InvalidModelFile(
+            source.display().to_string(),
+        ));
+    }
+
+    let path = entry.path(models_folder);
+    tokio::fs::copy(source, part_path(&path)).await?;
+    verify_and_install(
+        &path,
+        &source.display().to_string(),
+        entry.sha256.as_deref(),
+    )
+    .await
+}
+
+/// Folder given with `--seed-models <dir>` or `TRANSCENDIA_SEED_MODELS`, the flag wins.
+fn seed_models_dir() -> Option<PathBuf> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == SEED_MODELS_ARG {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(dir) = arg
+            .strip_prefix(SEED_MODELS_ARG)
+            .and_then(|a| a.strip_prefix('='))
+        {
+            return Some(PathBuf::from(dir));
+        }
+    }
+    std::env::var_os(SEED_MODELS_ENV).map(PathBuf::from)
+}
+
+/// Copies the files of the seed folder that are missing from the models folder, before
+/// `check_for_models` looks for them. Files already present are never replaced.
+pub fn seed_models(app: &AppHandle) {
+    let Some(seed_dir) = seed_models_dir() else {
+        return;
+    };
+    let models_folder = get_models_folder(app);
+    let manifest = load_manifest(&models_folder);
+    let copied = tauri::async_runtime::block_on(copy_missing(
+        &seed_dir,
+        &models_folder,
+        &models_folder,
+        &manifest,
+    ));
+    if let Err(err) = copied {
+        eprintln!("Could not seed models from '{}'", seed_dir.display());
+        report_error(app, "", &err);
+    }
+}
+
+/// Files go through the part file and the checksum of their manifest entry like a download,
+/// so an interrupted or corrupt copy is never taken for an installed model.
+async fn copy_missing(
+    from: &Path,
+    to: &Path,
+    models_folder: &Path,
+    manifest: &[ModelEntry],
+) -> Result<(), TranscendiaError> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            Box::pin(copy_missing(
+                &entry.path(),
+                &target,
+                models_folder,
+                manifest,
+            ))
+            .await?;
+        } else if !target.exists() && !entry.path().to_string_lossy().ends_with(PART_SUFFIX) {
+            let sha256 = manifest
+                .iter()
+                .find(|m| m.path(models_folder) == target)
+                .and_then(|m| m.sha256.as_deref());
+            eprintln!("Seeding {}", target.display());
+            tokio::fs::copy(entry.path(), part_path(&target)).await?;
+            verify_and_install(&target, &entry.path().display().to_string(), sha256).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Offline translation models are Marian/OPUS-MT models converted to `.rten`, one folder per
+/// language pair. Nobody hosts them converted, so they only come from a mirror or the seed
+/// folder.
+fn download_translation_model(
+    app: &AppHandle,
+    models_folder: &Path,
+    translator: &TranslatorConfig,
+    mirror: Option<&str>,
+) -> bool {
+    let TranslatorConfig::Offline { model } = translator else {
+        return true;
+    };
+
+    let model_dir = match translation_model_dir(models_folder, model) {
+        Ok(dir) => dir,
+        Err(err) => {
+            report_error(app, "", &err);
+            return false;
         }
+    };
+    let missing: Vec<&str> = TRANSLATION_MODEL_FILES
+        .into_iter()
+        .filter(|file| !model_dir.join(file).exists())
+        .collect();
+    if missing.is_empty() {
+        return true;
+    }
+    let Some(mirror) = mirror.map(str::trim).filter(|m| !m.is_empty()) else {
+        report_error(
+            app,
+            "",
+            &TranscendiaError::Download(format!(
+                "The offline model '{}' is missing, run scripts/convert-opus-mt.sh {} '{}' \
+                 or set a model mirror",
+                model,
+                model,
+                models_folder.display()
+            )),
+        );
+        return false;
+    };
 
-        app.unlisten(id);
+    if let Err(err) = fs::create_dir_all(&model_dir) {
+        report_error(app, "", &err.into());
+    }
+    let app = app.clone();
+    let folder_url = model_url(
+        Some(mirror),
+        &format!("{}/opus-mt-{}", TRANSLATION_MODEL_FOLDER_NAME, model),
+        String::new,
+    );
+    tauri::async_runtime::spawn(async move {
+        if let Err(err) = enqueue_translation_files(&app, &model_dir, &folder_url, &missing).await {
+            report_error(&app, "", &err);
+        }
     });
+
+    false
+}
+
+/// Queues the missing files of a translation model with the checksums the mirror lists for
+/// them. A file without a checksum is refused, the conversion script always writes them.
+async fn enqueue_translation_files(
+    app: &AppHandle,
+    model_dir: &Path,
+    folder_url: &str,
+    missing: &[&str],
+) -> Result<(), TranscendiaError> {
+    let checksums_url = format!("{}/{}", folder_url, TRANSLATION_MODEL_CHECKSUMS);
+    let checksums = fetch_text(&checksums_url).await?;
+    let downloads = app.state::<DownloadManager>();
+    for file in missing {
+        let sha256 = checksums
+            .lines()
+            .filter_map(|line| line.split_once(char::is_whitespace))
+            .find(|(_, name)| name.trim().trim_start_matches('*') == *file)
+            .map(|(sha256, _)| sha256)
+            .ok_or_else(|| {
+                TranscendiaError::Download(format!("'{}' is not listed in {}", file, checksums_url))
+            })?;
+        let url = format!("{}/{}", folder_url, file);
+        downloads.enqueue(app, model_dir.join(file), &url, Some(sha256))?;
+    }
+    Ok(())
 }
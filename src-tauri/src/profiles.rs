@@ -0,0 +1,353 @@
+// Copyright © 2025 Nantsa Montillet
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::commands::switch_profile;
+use crate::config::{CaptureRegion, Config, TranslatorConfig};
+use crate::errors::{lock, report_error, TranscendiaError};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+use tokio::time::{sleep, Duration};
+
+const PROFILES_FOLDER: &str = "profiles";
+const PROFILES_STATE_FILE: &str = "profiles.json";
+const PROFILE_EXTENSION: &str = "json";
+const FOCUS_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Serialize, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct ProfilesData {
+    pub active: Option<String>,
+    /// Switch profiles when the focused window matches one of their bindings.
+    pub auto_switch: bool,
+    /// Profile name to the app names or window titles it is bound to.
+    pub bindings: BTreeMap<String, Vec<String>>,
+}
+
+/// What a profile switches: the regions with their monitor, languages and interval, the
+/// overlay style and the translation backend. Hotkeys, OCR models, the model mirror, the
+/// thresholds and API keys stay in the global config.
+#[derive(Serialize, Clone)]
+pub struct Profile {
+    pub version: u32,
+    pub regions: Vec<CaptureRegion>,
+    pub text_color: String,
+    pub text_align: String,
+    pub text_size: u16,
+    pub background_color: String,
+    pub blur_background: bool,
+    pub preserve_layout: bool,
+    /// Written without its API key, see `Config::api_keys`.
+    pub translator: TranslatorConfig,
+}
+
+impl Profile {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            version: config.version,
+            regions: config.regions.clone(),
+            text_color: config.text_color.clone(),
+            text_align: config.text_align.clone(),
+            text_size: config.text_size,
+            background_color: config.background_color.clone(),
+            blur_background: config.blur_background,
+            preserve_layout: config.preserve_layout,
+            translator: config.translator.clone(),
+        }
+    }
+
+    /// The global config with this profile applied, its translator getting the global key.
+    pub fn apply(self, config: &Config) -> Config {
+        let mut config = config.clone();
+        config.remember_api_key(&config.translator.clone());
+        let mut translator = self.translator;
+        // Profiles saved as a whole config by older versions still hold their key.
+        if let Some(key) = translator.api_key() {
+            config
+                .api_keys
+                .entry(translator.kind().to_string())
+                .or_insert_with(|| key.to_string());
+        }
+        translator.set_api_key(config.api_keys.get(translator.kind()).cloned());
+
+        Config {
+            regions: self.regions,
+            text_color: self.text_color,
+            text_align: self.text_align,
+            text_size: self.text_size,
+            background_color: self.background_color,
+            blur_background: self.blur_background,
+            preserve_layout: self.preserve_layout,
+            translator,
+            ..config
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct ProfilesList {
+    #[serde(flatten)]
+    pub data: ProfilesData,
+    pub profiles: Vec<String>,
+}
+
+/// Active profile and automatic switching rules, persisted in `profiles.json`.
+pub struct ProfilesState(pub Mutex<ProfilesData>);
+
+impl ProfilesState {
+    pub fn load(app: &AppHandle) -> Self {
+        let mut data = fs::read_to_string(profiles_state_path(app))
+            .ok()
+            .and_then(|d| serde_json::from_str::<ProfilesData>(&d).ok())
+            .unwrap_or_default();
+        data.active = data
+            .active
+            .filter(|name| profile_path(app, name).is_ok_and(|p| p.exists()));
+
+        Self(Mutex::new(data))
+    }
+
+    pub fn data(&self) -> Result<ProfilesData, TranscendiaError> {
+        Ok(lock(&self.0, "profiles")?.clone())
+    }
+
+    pub fn active(&self) -> Result<Option<String>, TranscendiaError> {
+        Ok(lock(&self.0, "profiles")?.active.clone())
+    }
+
+    pub fn set_active(&self, app: &AppHandle, name: Option<String>) -> Result<(), TranscendiaError> {
+        self.update(app, |data| data.active = name)
+    }
+
+    /// Applies a change to the profiles data and writes it to disk.
+    pub fn update(
+        &self,
+        app: &AppHandle,
+        change: impl FnOnce(&mut ProfilesData),
+    ) -> Result<(), TranscendiaError> {
+        let mut data = lock(&self.0, "profiles")?;
+        change(&mut data);
+        let json = serde_json::to_string(&*data).map_err(|_| TranscendiaError::CannotSaveConfig)?;
+        fs::write(profiles_state_path(app), json)?;
+        Ok(())
+    }
+}
+
+fn app_config_dir(app: &AppHandle) -> PathBuf {
+    app.path()
+        .app_config_dir()
+        .expect("Could not get app config dir")
+}
+
+fn profiles_state_path(app: &AppHandle) -> PathBuf {
+    app_config_dir(app).join(PROFILES_STATE_FILE)
+}
+
+fn profiles_folder(app: &AppHandle) -> PathBuf {
+    app_config_dir(app).join(PROFILES_FOLDER)
+}
+
+/// Profile names become file names, so only a safe subset of characters is accepted.
+fn profile_path(app: &AppHandle, name: &str) -> Result<PathBuf, TranscendiaError> {
+    let name = name.trim();
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_'));
+    if !valid {
+        return Err(TranscendiaError::InvalidProfileName(name.to_string()));
+    }
+
+    Ok(profiles_folder(app).join(format!("{}.{}", name, PROFILE_EXTENSION)))
+}
+
+pub fn list_profiles(app: &AppHandle) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(profiles_folder(app)) else {
+        return Vec::new();
+    };
+
+    let mut profiles: Vec<String> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|e| e == PROFILE_EXTENSION))
+        .filter_map(|p| p.file_stem().map(|s| s.to_string_lossy().to_string()))
+        .collect();
+    profiles.sort_by_key(|p| p.to_lowercase());
+    profiles
+}
+
+/// Read as a config so the migrations run. Files written as a whole config, global settings
+/// and API key included, are rewritten with the profile fields only.
+pub fn load_profile(app: &AppHandle, name: &str) -> Result<Profile, TranscendiaError> {
+    let path = profile_path(app, name)?;
+    if !path.exists() {
+        return Err(TranscendiaError::ProfileNotFound(name.to_string()));
+    }
+    let profile = Profile::from_config(&Config::read_file(&path)?);
+    let json = profile_json(&profile)?;
+    if fs::read_to_string(&path)? != json {
+        fs::write(&path, json)?;
+    }
+    Ok(profile)
+}
+
+pub fn save_profile(
+    app: &AppHandle,
+    name: &str,
+    profile: &Profile,
+) -> Result<(), TranscendiaError> {
+    let path = profile_path(app, name)?;
+    fs::create_dir_all(profiles_folder(app))?;
+    fs::write(path, profile_json(profile)?)?;
+    Ok(())
+}
+
+fn profile_json(profile: &Profile) -> Result<String, TranscendiaError> {
+    let mut profile = profile.clone();
+    profile.translator.set_api_key(None);
+    serde_json::to_string(&profile).map_err(|_| TranscendiaError::CannotSaveConfig)
+}
+
+/// Fails instead of overwriting, used when the user picks a name for a new profile.
+pub fn create_profile(
+    app: &AppHandle,
+    name: &str,
+    profile: &Profile,
+) -> Result<(), TranscendiaError> {
+    if profile_path(app, name)?.exists() {
+        return Err(TranscendiaError::ProfileExists(name.to_string()));
+    }
+    save_profile(app, name, profile)
+}
+
+pub fn delete_profile(app: &AppHandle, name: &str) -> Result<(), TranscendiaError> {
+    let path = profile_path(app, name)?;
+    if !path.exists() {
+        return Err(TranscendiaError::ProfileNotFound(name.to_string()));
+    }
+    fs::remove_file(path)?;
+    Ok(())
+}
+
+/// First profile bound to the app name or title, bindings are case-insensitive substrings.
+fn matching_profile(data: &ProfilesData, app_name: &str, title: &str) -> Option<String> {
+    let (app_name, title) = (app_name.to_lowercase(), title.to_lowercase());
+    data.bindings
+        .iter()
+        .find(|(_, patterns)| {
+            patterns
+                .iter()
+                .map(|p| p.trim().to_lowercase())
+                .filter(|p| !p.is_empty())
+                .any(|p| app_name.contains(&p) || title.contains(&p))
+        })
+        .map(|(name, _)| name.clone())
+}
+
+/// App name and title of the focused window, ignoring Transcendia's own windows.
+fn focused_window() -> Option<(String, String)> {
+    let own_pid = std::process::id();
+    xcap::Window::all()
+        .ok()?
+        .into_iter()
+        .find(|w| w.is_focused().unwrap_or(false))
+        .filter(|w| w.pid().is_ok_and(|pid| pid != own_pid))
+        .map(|w| (w.app_name().unwrap_or_default(), w.title().unwrap_or_default()))
+}
+
+/// Polls the focused window and activates the profile bound to it.
+pub fn start_profile_watcher(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut last_window: Option<(String, String)> = None;
+        loop {
+            sleep(FOCUS_POLL_INTERVAL).await;
+
+            // A poisoned state stays poisoned, polling it again would repeat the error.
+            let data = match app.state::<ProfilesState>().data() {
+                Ok(data) => data,
+                Err(err) => {
+                    report_error(&app, "", &err);
+                    break;
+                }
+            };
+            if !data.auto_switch {
+                last_window = None;
+                continue;
+            }
+
+            // Listing windows blocks, it must not hold up an async worker every second.
+            let window = match tauri::async_runtime::spawn_blocking(focused_window).await {
+                Ok(Some(window)) => window,
+                Ok(None) => continue,
+                Err(err) => {
+                    eprintln!("Could not read the focused window: {}", err);
+                    continue;
+                }
+            };
+            if last_window.as_ref() == Some(&window) {
+                continue;
+            }
+
+            let profile = matching_profile(&data, &window.0, &window.1);
+            last_window = Some(window);
+            if let Some(profile) = profile.filter(|p| data.active.as_ref() != Some(p)) {
+                if let Err(err) = switch_profile(&app, &profile) {
+                    report_error(&app, "", &err);
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bindings(entries: &[(&str, &[&str])]) -> ProfilesData {
+        ProfilesData {
+            bindings: entries
+                .iter()
+                .map(|(name, patterns)| {
+                    (
+                        name.to_string(),
+                        patterns.iter().map(|p| p.to_string()).collect(),
+                    )
+                })
+                .collect(),
+            ..ProfilesData::default()
+        }
+    }
+
+    #[test]
+    fn matches_app_names_and_titles_case_insensitively() {
+        let data = bindings(&[("jrpg", &["Persona"]), ("vn", &["renpy"])]);
+
+        assert_eq!(
+            matching_profile(&data, "P5R.exe", "PERSONA 5 Royal"),
+            Some("jrpg".to_string())
+        );
+        assert_eq!(
+            matching_profile(&data, "RenPy", "Some novel"),
+            Some("vn".to_string())
+        );
+    }
+
+    #[test]
+    fn ignores_blank_patterns_and_unbound_windows() {
+        let data = bindings(&[("empty", &["", "  "]), ("jrpg", &["persona"])]);
+
+        assert_eq!(matching_profile(&data, "firefox", "News"), None);
+    }
+
+    #[test]
+    fn trims_patterns() {
+        let data = bindings(&[("vn", &[" renpy "])]);
+
+        assert_eq!(matching_profile(&data, "renpy", ""), Some("vn".to_string()));
+    }
+}
@@ -0,0 +1,143 @@
+// Copyright © 2025 Nantsa Montillet
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::errors::{lock, report_error, TranscendiaError};
+use crate::events::Events;
+use crate::translate_runtime::TranslateRuntime;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::time::{sleep, Duration};
+
+/// Weight of the newest sample in the averages.
+const AVERAGE_WEIGHT: f32 = 0.2;
+const STATS_EVENT_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Time spent in each stage for one translation, in milliseconds.
+#[derive(Serialize, Clone, Copy, Default)]
+pub struct StageLatency {
+    pub capture_ms: f32,
+    pub detect_ms: f32,
+    pub recognize_ms: f32,
+    pub translate_ms: f32,
+    pub emit_ms: f32,
+    /// From the capture to the overlay update, time spent waiting between stages included.
+    pub total_ms: f32,
+}
+
+impl StageLatency {
+    fn blend(&mut self, sample: &StageLatency) {
+        let mix = |avg: &mut f32, value: f32| *avg += (value - *avg) * AVERAGE_WEIGHT;
+        mix(&mut self.capture_ms, sample.capture_ms);
+        mix(&mut self.detect_ms, sample.detect_ms);
+        mix(&mut self.recognize_ms, sample.recognize_ms);
+        mix(&mut self.translate_ms, sample.translate_ms);
+        mix(&mut self.emit_ms, sample.emit_ms);
+        mix(&mut self.total_ms, sample.total_ms);
+    }
+}
+
+pub fn millis(duration: Duration) -> f32 {
+    duration.as_secs_f32() * 1000.0
+}
+
+/// Counters and timings of one region since its worker started.
+#[derive(Serialize, Clone, Default)]
+pub struct RegionStats {
+    pub region: String,
+    pub captures: u64,
+    /// Captures dropped before OCR because the frame barely changed.
+    pub unchanged_frames: u64,
+    pub translations: u64,
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+    /// Translations dropped in flight because newer text came in.
+    pub cancelled: u64,
+    pub request_errors: u64,
+    pub errors: u64,
+    /// Share of the detected lines that recognition turned into text, from 0 to 1. It is not a
+    /// confidence score, ocrs does not expose one.
+    pub recognized_line_ratio: Option<f32>,
+    pub last: StageLatency,
+    pub average: StageLatency,
+}
+
+/// Stats of every running region, shared by the stages of all workers.
+#[derive(Clone, Default)]
+pub struct RuntimeStats(Arc<Mutex<HashMap<String, RegionStats>>>);
+
+impl RuntimeStats {
+    /// Resets the stats of a region when its worker starts.
+    pub fn start(&self, region_id: &str) -> Result<(), TranscendiaError> {
+        let stats = RegionStats {
+            region: region_id.to_string(),
+            ..RegionStats::default()
+        };
+        self.regions()?.insert(region_id.to_string(), stats);
+        Ok(())
+    }
+
+    /// Ignored once the region was removed, stages can still finish a job after a stop.
+    /// Also ignored when the stats are poisoned, `snapshot` reports that to the diagnostics.
+    pub fn record(&self, region_id: &str, change: impl FnOnce(&mut RegionStats)) {
+        let Ok(mut regions) = self.regions() else {
+            return;
+        };
+        if let Some(stats) = regions.get_mut(region_id) {
+            change(stats);
+        }
+    }
+
+    /// Called once a translation reached the overlay.
+    pub fn record_latency(&self, region_id: &str, latency: StageLatency) {
+        self.record(region_id, |stats| {
+            if stats.translations == 0 {
+                stats.average = latency;
+            } else {
+                stats.average.blend(&latency);
+            }
+            stats.last = latency;
+            stats.translations += 1;
+        });
+    }
+
+    pub fn remove(&self, region_id: &str) -> Result<(), TranscendiaError> {
+        self.regions()?.remove(region_id);
+        Ok(())
+    }
+
+    pub fn snapshot(&self) -> Result<Vec<RegionStats>, TranscendiaError> {
+        let mut stats: Vec<RegionStats> = self.regions()?.values().cloned().collect();
+        stats.sort_by(|a, b| a.region.cmp(&b.region));
+        Ok(stats)
+    }
+
+    fn regions(&self) -> Result<MutexGuard<'_, HashMap<String, RegionStats>>, TranscendiaError> {
+        lock(&self.0, "runtime stats")
+    }
+}
+
+/// Pushes the stats to the config window while it is open, for the diagnostics view.
+pub fn start_stats_emitter(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            sleep(STATS_EVENT_INTERVAL).await;
+
+            if app.get_webview_window("config").is_none() {
+                continue;
+            }
+            let stats = match app.state::<TranslateRuntime>().stats() {
+                Ok(stats) => stats,
+                Err(err) => {
+                    report_error(&app, "", &err);
+                    break;
+                }
+            };
+            if let Err(err) = app.emit_to("config", Events::RuntimeStats.as_str(), stats) {
+                eprintln!("Could not emit runtime stats: {}", err);
+            }
+        }
+    });
+}
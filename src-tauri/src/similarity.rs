@@ -0,0 +1,67 @@
+// Copyright © 2025 Nantsa Montillet
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+/// Returns how close two texts are, from 0 (nothing in common) to 1 (identical),
+/// based on the Levenshtein distance between their characters.
+pub fn text_similarity(a: &str, b: &str) -> f32 {
+    let a: Vec<char> = a.chars().filter(|c| !c.is_whitespace()).collect();
+    let b: Vec<char> = b.chars().filter(|c| !c.is_whitespace()).collect();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+
+    1.0 - levenshtein(&a, &b) as f32 / longest as f32
+}
+
+fn levenshtein(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_texts_are_fully_similar() {
+        assert_eq!(text_similarity("Hello world", "Hello world"), 1.0);
+        assert_eq!(text_similarity("Hello world", " Hello\nworld "), 1.0);
+    }
+
+    #[test]
+    fn small_ocr_edits_stay_similar() {
+        let similarity = text_similarity(
+            "The quick brown fox jumps over the lazy dog",
+            "The quick brovvn fox jumps over the 1azy dog",
+        );
+
+        assert!(similarity > 0.9 && similarity < 1.0, "{}", similarity);
+    }
+
+    #[test]
+    fn empty_text_only_matches_empty_text() {
+        assert_eq!(text_similarity("", ""), 1.0);
+        assert_eq!(text_similarity(" \n", ""), 1.0);
+        assert_eq!(text_similarity("Hello", ""), 0.0);
+        assert_eq!(text_similarity("", "Hello"), 0.0);
+    }
+
+    #[test]
+    fn different_texts_are_not_similar() {
+        let similarity = text_similarity("Where are you going?", "Nice weather today");
+
+        assert!(similarity < 0.5, "{}", similarity);
+    }
+}
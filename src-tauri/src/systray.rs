@@ -1,22 +1,29 @@
 // Copyright © 2025 Nantsa Montillet
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
+use crate::commands::{change_runtime_mode, switch_profile};
+use crate::errors::report_error;
 use crate::events::Events;
+use crate::profiles::{list_profiles, ProfilesState};
+use crate::translate_runtime::{RuntimeMode, TranslateRuntime};
 use crate::windows::create_config_window;
-use tauri::menu::{Menu, MenuItem};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tauri::menu::{CheckMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu};
 use tauri::tray::{TrayIcon, TrayIconBuilder};
-use tauri::{App, Listener};
+use tauri::{AppHandle, Listener, Manager};
 
-pub fn create_systray(app: &App) -> Result<TrayIcon, tauri::Error> {
-    let config_item_on = MenuItem::with_id(app, "config", "Configuration", true, None::<&str>)?;
-    let config_item_off = MenuItem::with_id(app, "config", "Configuration", false, None::<&str>)?;
-    let quit_item = MenuItem::with_id(app, "quit", "Quit Transcendia", true, None::<&str>)?;
-    let menu_on = Menu::with_items(app, &[&config_item_on, &quit_item])?;
-    let menu_off = Menu::with_items(app, &[&config_item_off, &quit_item])?;
+const PROFILE_ITEM_PREFIX: &str = "profile:";
+const MODE_ITEMS: [(&str, &str, RuntimeMode); 3] = [
+    ("mode:running", "Translate continuously", RuntimeMode::Running),
+    ("mode:oneshot", "Translate on demand", RuntimeMode::OneShot),
+    ("mode:paused", "Pause", RuntimeMode::Paused),
+];
 
+pub fn create_systray(app: &AppHandle) -> Result<TrayIcon, tauri::Error> {
     let tray = TrayIconBuilder::new()
         .icon(app.default_window_icon().unwrap().clone())
-        .menu(&menu_on)
+        .menu(&build_menu(app, true)?)
         .show_menu_on_left_click(true)
         .on_menu_event(|app, event| match event.id.as_ref() {
             "config" => {
@@ -27,20 +34,102 @@ pub fn create_systray(app: &App) -> Result<TrayIcon, tauri::Error> {
             "quit" => {
                 app.exit(0);
             }
-            _ => {
-                println!("Menu item {:?} not handled", event.id);
+            "translate_once" => {
+                if let Err(err) = app.state::<TranslateRuntime>().retranslate() {
+                    report_error(app, "", &err);
+                }
+            }
+            id if id.starts_with("mode:") => {
+                let mode = MODE_ITEMS.iter().find(|(item_id, ..)| *item_id == id);
+                if let Some((_, _, mode)) = mode {
+                    if let Err(err) = change_runtime_mode(app, *mode) {
+                        report_error(app, "", &err);
+                    }
+                }
             }
+            id => match id.strip_prefix(PROFILE_ITEM_PREFIX) {
+                Some(name) => {
+                    if let Err(err) = switch_profile(app, name) {
+                        report_error(app, "", &err);
+                    }
+                }
+                None => println!("Menu item {:?} not handled", event.id),
+            },
         })
         .build(app)?;
 
-    let tray_clone = tray.clone();
+    let config_enabled = Arc::new(AtomicBool::new(true));
+
+    let (tray_clone, app_clone, enabled) = (tray.clone(), app.clone(), config_enabled.clone());
     app.listen(Events::OnOffConfigTrayItem.as_str(), move |event| {
-        if event.payload() == "true" {
-            tray_clone.set_menu(Some(menu_on.clone())).unwrap();
-        } else {
-            tray_clone.set_menu(Some(menu_off.clone())).unwrap();
-        }
+        enabled.store(event.payload() == "true", Ordering::Relaxed);
+        refresh_menu(&app_clone, &tray_clone, enabled.load(Ordering::Relaxed));
     });
 
+    for event in [Events::ProfilesChanged, Events::RuntimeModeChanged] {
+        let (tray_clone, app_clone, enabled) = (tray.clone(), app.clone(), config_enabled.clone());
+        app.listen(event.as_str(), move |_| {
+            refresh_menu(&app_clone, &tray_clone, enabled.load(Ordering::Relaxed));
+        });
+    }
+
     Ok(tray)
 }
+
+fn refresh_menu(app: &AppHandle, tray: &TrayIcon, config_enabled: bool) {
+    let result = build_menu(app, config_enabled).and_then(|menu| tray.set_menu(Some(menu)));
+    if let Err(err) = result {
+        eprintln!("Failed to refresh tray menu : {:?}", err);
+    }
+}
+
+fn build_menu(app: &AppHandle, config_enabled: bool) -> Result<Menu<tauri::Wry>, tauri::Error> {
+    // Like the mode below, a poisoned profiles state shows no active profile.
+    let active = app.state::<ProfilesState>().active().unwrap_or(None);
+    let profiles_menu = Submenu::new(app, "Profiles", true)?;
+    let profiles = list_profiles(app);
+    if profiles.is_empty() {
+        profiles_menu.append(&MenuItem::new(app, "No saved profile", false, None::<&str>)?)?;
+    }
+    for name in profiles {
+        let checked = active.as_deref() == Some(name.as_str());
+        profiles_menu.append(&CheckMenuItem::with_id(
+            app,
+            format!("{}{}", PROFILE_ITEM_PREFIX, name),
+            &name,
+            true,
+            checked,
+            None::<&str>,
+        )?)?;
+    }
+
+    // A runtime broken by a poisoned lock no longer translates, it shows as paused.
+    let mode = app
+        .state::<TranslateRuntime>()
+        .mode()
+        .unwrap_or(RuntimeMode::Paused);
+    let translate_item = MenuItem::with_id(
+        app,
+        "translate_once",
+        "Translate now",
+        mode != RuntimeMode::Paused,
+        None::<&str>,
+    )?;
+    let menu = Menu::with_items(app, &[&translate_item])?;
+    for (id, label, item_mode) in MODE_ITEMS {
+        let checked = mode == item_mode;
+        menu.append(&CheckMenuItem::with_id(app, id, label, true, checked, None::<&str>)?)?;
+    }
+
+    let config_item =
+        MenuItem::with_id(app, "config", "Configuration", config_enabled, None::<&str>)?;
+    let quit_item = MenuItem::with_id(app, "quit", "Quit Transcendia", true, None::<&str>)?;
+    menu.append_items(&[
+        &PredefinedMenuItem::separator(app)?,
+        &config_item,
+        &profiles_menu,
+        &PredefinedMenuItem::separator(app)?,
+        &quit_item,
+    ])?;
+    Ok(menu)
+}
@@ -1,181 +1,810 @@
 // Copyright © 2025 Nantsa Montillet
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
-use crate::config::Region;
+use crate::capture::{CaptureRequest, CaptureWorker, Frame};
+use crate::config::{CaptureRegion, Config, OcrModels, Region, TranslatorConfig, WindowTarget};
+use crate::errors::{lock, report_error, TranscendiaError};
 use crate::events::Events;
-use crate::ocr_models::{DETECTION_MODEL_NAME, MODEL_FOLDER_NAME, RECOGNITION_MODEL_NAME};
-use image::DynamicImage;
-use ocrs::{ImageSource, OcrEngine, OcrEngineParams};
-use reqwest::{Client, Url};
-use rten::Model;
-use serde_json::Value;
-use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
-use std::sync::Arc;
+use crate::model_manifest::{find_model, ModelKind};
+use crate::ocr::OcrWorker;
+use crate::ocr_models::get_models_folder;
+use crate::runtime_stats::{millis, RegionStats, RuntimeStats, StageLatency};
+use crate::similarity::text_similarity;
+use crate::translation_cache::TranslationCache;
+use crate::translators::{Backend, TranslateError, Translator, AUTO_DETECT_LANG};
+use crate::windows::{edit_overlay, overlay_label};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
 use tauri::{AppHandle, Emitter, Manager};
 use tauri_plugin_macos_permissions::{
     check_screen_recording_permission, request_screen_recording_permission,
 };
-use tokio::sync::Notify;
-use tokio::time::{sleep, Duration};
+use tokio::sync::{watch, Notify};
+use tokio::time::{sleep_until, Duration, Instant};
+
+/// Shortest delay between two captures, whatever the config or the adaptive pace says.
+const MIN_INTERVAL: Duration = Duration::from_millis(100);
+/// Adaptive workers go down to a quarter of the interval and up to this multiple of it.
+const ADAPTIVE_MAX_FACTOR: u32 = 8;
+
+#[derive(Clone, PartialEq)]
+pub struct TranslationSettings {
+    pub translator: TranslatorConfig,
+    pub source_lang: String,
+    pub target_lang: String,
+    pub similarity_threshold: f32,
+    pub frame_change_threshold: f32,
+    pub ocr_models: OcrModels,
+}
+
+impl TranslationSettings {
+    pub fn new(region: &CaptureRegion, config: &Config) -> Self {
+        Self {
+            translator: config.translator.clone(),
+            source_lang: region.source_lang.clone(),
+            target_lang: region.lang.clone(),
+            similarity_threshold: config.similarity_threshold,
+            frame_change_threshold: config.frame_change_threshold,
+            ocr_models: config.ocr_models.clone(),
+        }
+    }
+}
+
+/// A recognised line, positioned in logical pixels relative to the capture region.
+#[derive(Serialize, Clone)]
+pub struct TextBlock {
+    pub text: String,
+    pub x: f32,
+    pub y: f32,
+    pub w: f32,
+    pub h: f32,
+}
+
+#[derive(Serialize, Clone)]
+pub struct TranslatedText {
+    pub region: String,
+    pub text: String,
+    pub blocks: Vec<TextBlock>,
+    pub source_lang: Option<String>,
+    pub target_lang: String,
+}
+
+/// How workers are driven: on their interval, not at all, or only when triggered.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum RuntimeMode {
+    Running,
+    Paused,
+    OneShot,
+}
+
+struct RegionWorker {
+    need_stop: Arc<Notify>,
+    trigger: Arc<Notify>,
+    interval_ms: Arc<AtomicU32>,
+    adaptive: Arc<AtomicBool>,
+    settings: Arc<Mutex<TranslationSettings>>,
+}
 
 pub struct TranslateRuntime {
-    pub need_stop: Arc<Notify>,
-    pub is_running: Arc<AtomicBool>,
-    pub interval: Arc<AtomicU8>,
+    workers: Mutex<HashMap<String, RegionWorker>>,
+    cache: Arc<Mutex<TranslationCache>>,
+    mode: Arc<Mutex<RuntimeMode>>,
+    last_translation: Arc<Mutex<Option<String>>>,
+    stats: RuntimeStats,
+}
+
+impl TranslateRuntime {
+    pub fn new(cache: TranslationCache) -> Self {
+        Self {
+            workers: Mutex::new(HashMap::new()),
+            cache: Arc::new(Mutex::new(cache)),
+            mode: Arc::new(Mutex::new(RuntimeMode::Running)),
+            last_translation: Arc::new(Mutex::new(None)),
+            stats: RuntimeStats::default(),
+        }
+    }
+
+    pub fn cache(&self) -> Arc<Mutex<TranslationCache>> {
+        self.cache.clone()
+    }
+
+    pub fn mode(&self) -> Result<RuntimeMode, TranscendiaError> {
+        Ok(*lock(&self.mode, "runtime mode")?)
+    }
+
+    /// Paused and one-shot workers keep their overlay and last translation on screen.
+    pub fn set_mode(&self, mode: RuntimeMode) -> Result<(), TranscendiaError> {
+        *lock(&self.mode, "runtime mode")? = mode;
+        Ok(())
+    }
+
+    /// Runs every worker once now, ignoring the change thresholds and the cache.
+    /// This is the only way one-shot workers capture anything.
+    pub fn retranslate(&self) -> Result<(), TranscendiaError> {
+        for worker in self.workers()?.values() {
+            worker.trigger.notify_one();
+        }
+        Ok(())
+    }
+
+    pub fn last_translation(&self) -> Result<Option<String>, TranscendiaError> {
+        Ok(lock(&self.last_translation, "last translation")?.clone())
+    }
+
+    pub fn stats(&self) -> Result<Vec<RegionStats>, TranscendiaError> {
+        self.stats.snapshot()
+    }
+
+    pub fn is_running(&self, region_id: &str) -> Result<bool, TranscendiaError> {
+        Ok(self.workers()?.contains_key(region_id))
+    }
+
+    fn workers(&self) -> Result<MutexGuard<'_, HashMap<String, RegionWorker>>, TranscendiaError> {
+        lock(&self.workers, "region worker")
+    }
+
+    /// Removes a worker whose capture stage ended, unless a new one already took its place.
+    fn forget_worker(
+        &self,
+        region_id: &str,
+        need_stop: &Arc<Notify>,
+    ) -> Result<(), TranscendiaError> {
+        let mut workers = self.workers()?;
+        if workers
+            .get(region_id)
+            .is_some_and(|w| Arc::ptr_eq(&w.need_stop, need_stop))
+        {
+            workers.remove(region_id);
+            self.stats.remove(region_id)?;
+        }
+        Ok(())
+    }
+
+    /// Applies interval and language changes to a running worker without restarting it.
+    pub fn update(&self, region: &CaptureRegion, config: &Config) -> Result<(), TranscendiaError> {
+        if let Some(worker) = self.workers()?.get(&region.id) {
+            worker
+                .interval_ms
+                .store(region.interval_ms, Ordering::SeqCst);
+            worker.adaptive.store(region.adaptive, Ordering::SeqCst);
+            *lock(&worker.settings, "region settings")? = TranslationSettings::new(region, config);
+        }
+        Ok(())
+    }
 }
 
 pub fn start_translate_runtime(
     app_handle: &AppHandle,
     data: &TranslateRuntime,
+    capture_region: &CaptureRegion,
+    config: &Config,
+) -> Result<(), TranscendiaError> {
+    let Some(region) = capture_region.region.clone() else {
+        return Ok(());
+    };
+
+    let mut workers = data.workers()?;
+    if workers.contains_key(&capture_region.id) {
+        return Ok(());
+    }
+
+    let worker = RegionWorker {
+        need_stop: Arc::new(Notify::default()),
+        trigger: Arc::new(Notify::default()),
+        interval_ms: Arc::new(AtomicU32::new(capture_region.interval_ms)),
+        adaptive: Arc::new(AtomicBool::new(capture_region.adaptive)),
+        settings: Arc::new(Mutex::new(TranslationSettings::new(capture_region, config))),
+    };
+    let models_folder = get_models_folder(app_handle);
+    data.stats.start(&capture_region.id)?;
+
+    // Each stage only keeps the newest job of the previous one, so a slow stage skips
+    // intermediate frames instead of delaying the next capture.
+    let (frame_tx, frame_rx) = watch::channel(None);
+    let (text_tx, text_rx) = watch::channel(None);
+
+    let capture = CaptureStage {
+        app_handle: app_handle.clone(),
+        region_id: capture_region.id.clone(),
+        monitor: capture_region.monitor,
+        window: capture_region.window.clone(),
+        region,
+        capturer: None,
+        settings: TranslationSettings::new(capture_region, config),
+        shared_settings: worker.settings.clone(),
+        interval_ms: worker.interval_ms.clone(),
+        adaptive: worker.adaptive.clone(),
+        need_stop: worker.need_stop.clone(),
+        trigger: worker.trigger.clone(),
+        mode: data.mode.clone(),
+        stats: data.stats.clone(),
+    };
+    let ocr = OcrStage {
+        app_handle: app_handle.clone(),
+        region_id: capture_region.id.clone(),
+        models_folder: models_folder.clone(),
+        ocr: None,
+        settings: None,
+        old_text: String::new(),
+        stats: data.stats.clone(),
+    };
+    let translate = TranslateStage {
+        app_handle: app_handle.clone(),
+        region_id: capture_region.id.clone(),
+        models_folder,
+        translator: None,
+        cache: data.cache.clone(),
+        last_translation: data.last_translation.clone(),
+        stats: data.stats.clone(),
+    };
+
+    tauri::async_runtime::spawn(capture.run(frame_tx));
+    tauri::async_runtime::spawn(ocr.run(frame_rx, text_tx));
+    tauri::async_runtime::spawn(translate.run(text_rx));
+
+    workers.insert(capture_region.id.clone(), worker);
+    Ok(())
+}
+
+pub fn stop_translate_runtime(
+    data: &TranslateRuntime,
+    region_id: &str,
+) -> Result<(), TranscendiaError> {
+    if let Some(worker) = data.workers()?.remove(region_id) {
+        worker.need_stop.notify_one();
+    }
+    data.stats.remove(region_id)
+}
+
+/// Delay between the start of two captures of a worker.
+#[derive(Default)]
+struct Cadence {
+    delay: Option<Duration>,
+}
+
+impl Cadence {
+    /// Adaptive workers halve their delay when the frame changed and grow it by half when the
+    /// region stayed the same, within `interval / 4` and `interval * ADAPTIVE_MAX_FACTOR`.
+    fn next(&mut self, interval: Duration, adaptive: bool, changed: bool) -> Duration {
+        let interval = interval.max(MIN_INTERVAL);
+        if !adaptive {
+            self.delay = None;
+            return interval;
+        }
+
+        let fastest = (interval / 4).max(MIN_INTERVAL);
+        let slowest = interval * ADAPTIVE_MAX_FACTOR;
+        let delay = self.delay.unwrap_or(interval);
+        let delay = if changed { delay / 2 } else { delay * 3 / 2 };
+        let delay = delay.clamp(fastest, slowest);
+        self.delay = Some(delay);
+        delay
+    }
+}
+
+/// A changed frame, sent from the capture stage to the OCR stage.
+#[derive(Clone)]
+struct CaptureJob {
+    frame: Arc<Frame>,
+    settings: TranslationSettings,
+    /// Set by `TranslateRuntime::retranslate`, skips the similarity check and the cache.
+    forced: bool,
+    captured_at: Instant,
+    latency: StageLatency,
+}
+
+/// Recognised text, sent from the OCR stage to the translation stage.
+#[derive(Clone)]
+struct TextJob {
+    blocks: Vec<TextBlock>,
+    text: String,
+    settings: TranslationSettings,
+    forced: bool,
+    captured_at: Instant,
+    latency: StageLatency,
+}
+
+/// Captures the region on its cadence and publishes the frames that changed.
+struct CaptureStage {
+    app_handle: AppHandle,
+    region_id: String,
     monitor: u32,
+    window: Option<WindowTarget>,
     region: Region,
-    lang: String,
-) {
-    let app_handle = app_handle.clone();
-    let interval = data.interval.clone();
-    let need_stop = data.need_stop.clone();
+    capturer: Option<CaptureWorker>,
+    settings: TranslationSettings,
+    shared_settings: Arc<Mutex<TranslationSettings>>,
+    interval_ms: Arc<AtomicU32>,
+    adaptive: Arc<AtomicBool>,
+    need_stop: Arc<Notify>,
+    trigger: Arc<Notify>,
+    mode: Arc<Mutex<RuntimeMode>>,
+    stats: RuntimeStats,
+}
 
-    if data.is_running.load(Ordering::Relaxed) {
-        return;
+impl CaptureStage {
+    /// Stopping drops `frames`, which ends the OCR stage and then the translation stage.
+    async fn run(mut self, frames: watch::Sender<Option<CaptureJob>>) {
+        self.capture_loop(&frames).await;
+        // Also reached when the stage gives up by itself, the region must not look running.
+        if let Err(err) = self
+            .app_handle
+            .state::<TranslateRuntime>()
+            .forget_worker(&self.region_id, &self.need_stop)
+        {
+            report_error(&self.app_handle, &self.region_id, &err);
+        }
     }
 
-    tauri::async_runtime::spawn(async move {
+    async fn capture_loop(&mut self, frames: &watch::Sender<Option<CaptureJob>>) {
         #[cfg(target_os = "macos")]
         if !check_screen_recording_permission().await {
-            println!("No permission for screen capture !");
+            report_error(
+                &self.app_handle,
+                &self.region_id,
+                &TranscendiaError::NoCapturePermission,
+            );
             request_screen_recording_permission().await;
             return;
         }
 
-        let monitors = xcap::Monitor::all().unwrap();
-        let monitor = monitors
-            .iter()
-            .find(|m| m.id().expect("Can't get monitor name") == monitor)
-            .unwrap_or(monitors.get(0).expect("Cannot find any monitor"));
-
-        let models_folder = app_handle
-            .path()
-            .app_config_dir()
-            .expect("Could not get app config dir")
-            .join(MODEL_FOLDER_NAME);
-
-        let detection_model = Model::load_file(models_folder.join(DETECTION_MODEL_NAME))
-            .expect("Could not load detection model");
-        let recognition_model = Model::load_file(models_folder.join(RECOGNITION_MODEL_NAME))
-            .expect("Could not load recognition model");
-
-        let engine = OcrEngine::new(OcrEngineParams {
-            detection_model: Some(detection_model),
-            recognition_model: Some(recognition_model),
-            ..Default::default()
-        })
-            .expect("Impossible to create OCR engine");
-
-        let mut old_text = String::new();
-
-        let client = Client::builder()
-            .connect_timeout(Duration::from_secs(10))
-            .timeout(Duration::from_secs(20))
-            .https_only(true)
-            .build()
-            .expect("Could not create HTTP client");
-
+        let mut cadence = Cadence::default();
+        let mut next_tick = Instant::now();
         loop {
+            let interval = Duration::from_millis(self.interval_ms.load(Ordering::Relaxed) as u64);
+            let mut forced = false;
             tokio::select! {
-                _ = need_stop.notified() => {
+                _ = self.need_stop.notified() => {
                     break;
                 }
-                _ = sleep(Duration::from_secs(interval.load(Ordering::Relaxed) as u64)) => {
-                    let mut text = take_and_process_screenshot(monitor, &region, &engine);
-                    if text == old_text {
-                        continue;
-                    } else {
-                        old_text = text.clone();
+                _ = self.trigger.notified() => {
+                    forced = true;
+                }
+                _ = sleep_until(next_tick) => {
+                    match lock(&self.mode, "runtime mode") {
+                        Ok(mode) if *mode == RuntimeMode::Running => {}
+                        Ok(_) => {
+                            next_tick = Instant::now() + interval.max(MIN_INTERVAL);
+                            continue;
+                        }
+                        Err(err) => {
+                            report_error(&self.app_handle, &self.region_id, &err);
+                            break;
+                        }
                     }
+                }
+            }
+
+            let started = Instant::now();
+            let settings = match lock(&self.shared_settings, "region settings") {
+                Ok(settings) => settings.clone(),
+                Err(err) => {
+                    report_error(&self.app_handle, &self.region_id, &err);
+                    break;
+                }
+            };
+            let fresh = forced || settings != self.settings;
+            self.settings = settings;
 
-                    translate_text(&mut text, &lang, &client).await;
+            let result = self.capture(fresh).await;
+            // Measured from the start of the capture, so a slow capture eats into the wait.
+            let delay = cadence.next(
+                interval,
+                self.adaptive.load(Ordering::Relaxed),
+                matches!(result, Ok(Some(_))),
+            );
+            next_tick = started + delay;
 
-                    app_handle.emit(Events::NewTranslatedText.as_str(), text).unwrap();
+            self.stats.record(&self.region_id, |stats| {
+                stats.captures += 1;
+                match &result {
+                    Ok(Some(_)) => {}
+                    Ok(None) => stats.unchanged_frames += 1,
+                    Err(_) => stats.errors += 1,
+                }
+            });
+            match result {
+                Ok(Some(frame)) => {
+                    frames.send_replace(Some(CaptureJob {
+                        frame,
+                        settings: self.settings.clone(),
+                        forced,
+                        captured_at: started,
+                        latency: StageLatency {
+                            capture_ms: millis(started.elapsed()),
+                            ..StageLatency::default()
+                        },
+                    }));
                 }
+                Ok(None) => {}
+                Err(err) => report_error(&self.app_handle, &self.region_id, &err),
             }
         }
-    });
+    }
 
-    data.is_running.store(true, Ordering::Release);
+    /// The captured frame, or `None` when it barely changed since the last one sent.
+    async fn capture(&mut self, fresh: bool) -> Result<Option<Arc<Frame>>, TranscendiaError> {
+        let capturer = match &mut self.capturer {
+            Some(capturer) if capturer.is_alive() => capturer,
+            _ => self.capturer.insert(CaptureWorker::spawn(
+                &self.region_id,
+                self.monitor,
+                self.window.clone(),
+                self.region.clone(),
+            )?),
+        };
+
+        let capture = capturer
+            .capture(CaptureRequest {
+                fresh,
+                frame_change_threshold: self.settings.frame_change_threshold,
+            })
+            .await?;
+        if let Some((origin, region)) = capture.moved_placement {
+            if let Some(w) = self
+                .app_handle
+                .get_webview_window(&overlay_label(&self.region_id))
+            {
+                edit_overlay(&w, &region, origin, false)?;
+            }
+        }
+
+        Ok(capture.frame)
+    }
 }
 
-pub fn stop_translate_runtime(data: &TranslateRuntime) {
-    data.need_stop.notify_one();
-    data.is_running.store(false, Ordering::Release);
+/// Runs OCR on the newest frame and publishes the text when it changed.
+struct OcrStage {
+    app_handle: AppHandle,
+    region_id: String,
+    models_folder: PathBuf,
+    ocr: Option<OcrWorker>,
+    settings: Option<TranslationSettings>,
+    old_text: String,
+    stats: RuntimeStats,
 }
 
-fn take_and_process_screenshot(
-    monitor: &xcap::Monitor,
-    region: &Region,
-    engine: &OcrEngine,
-) -> String {
-    let capture = monitor.capture_image().expect("Screen capture failed");
-    let sf = monitor.scale_factor().expect("Can't get scale factor");
-    let cropped_image = DynamicImage::ImageRgba8(capture)
-        .crop_imm(
-            (region.x as f32 * sf) as u32,
-            (region.y as f32 * sf) as u32,
-            (region.w as f32 * sf) as u32,
-            (region.h as f32 * sf) as u32,
-        )
-        .to_rgb8();
+impl OcrStage {
+    async fn run(
+        mut self,
+        mut frames: watch::Receiver<Option<CaptureJob>>,
+        texts: watch::Sender<Option<TextJob>>,
+    ) {
+        while frames.changed().await.is_ok() {
+            let Some(job) = frames.borrow_and_update().clone() else {
+                continue;
+            };
+            match self.recognize(job).await {
+                Ok(Some(text)) => {
+                    texts.send_replace(Some(text));
+                }
+                Ok(None) => {}
+                Err(err) => {
+                    self.stats
+                        .record(&self.region_id, |stats| stats.errors += 1);
+                    report_error(&self.app_handle, &self.region_id, &err);
+                }
+            }
+        }
+    }
 
-    let img_source =
-        ImageSource::from_bytes(cropped_image.as_raw(), cropped_image.dimensions()).unwrap();
-    let ocr_input = engine
-        .prepare_input(img_source)
-        .expect("Could not prepare input for OCR");
+    /// The recognised text, or `None` when it is too similar to the last one sent.
+    async fn recognize(&mut self, job: CaptureJob) -> Result<Option<TextJob>, TranscendiaError> {
+        if self.settings.as_ref() != Some(&job.settings) {
+            let previous = self.settings.replace(job.settings.clone());
+            if previous.is_some_and(|s| s.ocr_models != job.settings.ocr_models) {
+                self.ocr = None;
+            }
+            self.old_text.clear();
+        }
+        if !self.ocr.as_ref().is_some_and(OcrWorker::is_alive) {
+            let OcrModels {
+                detection,
+                recognition,
+            } = &job.settings.ocr_models;
+            let detection = find_model(&self.models_folder, detection, ModelKind::Detection)?;
+            let recognition = find_model(&self.models_folder, recognition, ModelKind::Recognition)?;
+            self.ocr = Some(OcrWorker::spawn(
+                &self.region_id,
+                detection.path(&self.models_folder),
+                recognition.path(&self.models_folder),
+            )?);
+        }
+        let Some(ocr) = &mut self.ocr else {
+            return Ok(None);
+        };
 
-    let world_rects = engine.detect_words(&ocr_input).unwrap();
-    let line_rects = engine.find_text_lines(&ocr_input, &world_rects);
-    let line_texts = engine
-        .recognize_text(&ocr_input, &line_rects)
-        .expect("Could not recognize text");
+        let recognition = ocr.recognize(job.frame).await?;
+        if let Some(ratio) = recognition.recognized_line_ratio {
+            self.stats.record(&self.region_id, |stats| {
+                stats.recognized_line_ratio = Some(ratio)
+            });
+        }
+        let blocks = recognition.blocks;
+        let text = join_blocks(&blocks);
+        let similarity = text_similarity(&text, &self.old_text);
+        if !job.forced && similarity >= job.settings.similarity_threshold {
+            return Ok(None);
+        }
+        self.old_text = text.clone();
 
-    let mut text_buffer = String::from("");
-    for line in line_texts
-        .iter()
-        .flatten()
-        .filter(|l| l.to_string().len() > 1)
-    {
-        text_buffer.push_str(format!("{}\n", line).as_str());
+        Ok(Some(TextJob {
+            blocks,
+            text,
+            settings: job.settings,
+            forced: job.forced,
+            captured_at: job.captured_at,
+            latency: StageLatency {
+                detect_ms: millis(recognition.detect_time),
+                recognize_ms: millis(recognition.recognize_time),
+                ..job.latency
+            },
+        }))
     }
+}
 
-    text_buffer
+/// Translates the newest text and updates the overlay, dropping a request once it is stale.
+struct TranslateStage {
+    app_handle: AppHandle,
+    region_id: String,
+    models_folder: PathBuf,
+    translator: Option<(TranslatorConfig, Backend, Client)>,
+    cache: Arc<Mutex<TranslationCache>>,
+    last_translation: Arc<Mutex<Option<String>>>,
+    stats: RuntimeStats,
 }
 
-async fn translate_text(text: &mut String, target_lang: &String, client: &Client) {
-    let mut url = Url::parse(
-        format!(
-            "https://translate.googleapis.com/translate_a/single?client=gtx&sl=auto&tl={}&dt=t",
-            target_lang
-        )
-            .as_str(),
-    )
-        .unwrap();
-    url.query_pairs_mut().append_pair("q", text);
-    let res = client.get(url).send().await;
-
-    if let Ok(r) = res {
-        let res_text = r.text().await.expect("Could not read response");
-
-        let json = serde_json::from_str::<Value>(&res_text).expect("Could not parse json");
-        if let Some(values) = json.get(0).and_then(|v| v.as_array()).map(|arr| {
-            arr.iter()
-                .filter_map(|i| i.get(0).and_then(|t| t.as_str()))
-                .collect::<Vec<&str>>()
-        }) {
-            (*text).clear();
-            for value in values {
-                (*text).push_str(value);
+impl TranslateStage {
+    async fn run(mut self, mut texts: watch::Receiver<Option<TextJob>>) {
+        let mut next: Option<TextJob> = None;
+        loop {
+            let job = match next.take() {
+                Some(job) => job,
+                None => {
+                    if texts.changed().await.is_err() {
+                        break;
+                    }
+                    let Some(job) = texts.borrow_and_update().clone() else {
+                        continue;
+                    };
+                    job
+                }
+            };
+
+            // Newer text makes the request in flight useless, dropping its future cancels it.
+            tokio::select! {
+                result = self.translate(&job) => match result {
+                    Ok((translated, latency)) => self.emit(translated, latency, job.captured_at),
+                    Err(err) => {
+                        self.stats.record(&self.region_id, |stats| stats.errors += 1);
+                        report_error(&self.app_handle, &self.region_id, &err);
+                    }
+                },
+                changed = texts.changed() => {
+                    if changed.is_err() {
+                        break;
+                    }
+                    self.stats.record(&self.region_id, |stats| stats.cancelled += 1);
+                    next = texts.borrow_and_update().clone();
+                }
+            }
+        }
+    }
+
+    async fn translate(
+        &mut self,
+        job: &TextJob,
+    ) -> Result<(TranslatedText, StageLatency), TranscendiaError> {
+        let started = Instant::now();
+        let TranslationSettings {
+            translator,
+            source_lang,
+            target_lang,
+            ..
+        } = &job.settings;
+
+        let (_, backend, client) = match self.translator.take() {
+            Some(current) if current.0 == *translator => self.translator.insert(current),
+            _ => {
+                let backend = Backend::from_config(translator, &self.models_folder)?;
+                let client = backend.build_client().map_err(TranslateError::from)?;
+                self.translator
+                    .insert((translator.clone(), backend, client))
             }
+        };
+
+        let pinned_lang = Some(source_lang.clone()).filter(|l| l != AUTO_DETECT_LANG);
+        let cache_key = TranslationCache::key(translator, source_lang, target_lang, &job.text);
+        let cached = if job.forced {
+            None
         } else {
-            eprintln!("Could not find translated text in response");
+            lock(&self.cache, "translation cache")?.get(&cache_key)
+        };
+        self.stats.record(&self.region_id, |stats| {
+            if cached.is_some() {
+                stats.cache_hits += 1;
+            } else {
+                stats.cache_misses += 1;
+            }
+        });
+        let result = match cached {
+            Some(t) => Ok(t),
+            None => {
+                let result = backend
+                    .translate(client, &job.text, source_lang, target_lang)
+                    .await;
+                if let Ok(t) = &result {
+                    lock(&self.cache, "translation cache")?.insert(cache_key, t);
+                }
+                result
+            }
+        };
+        let latency = StageLatency {
+            translate_ms: millis(started.elapsed()),
+            ..job.latency
+        };
+
+        // A failed translation still shows the recognised text, the error goes to the UI.
+        let translated = match result {
+            Ok(t) => TranslatedText {
+                region: self.region_id.clone(),
+                blocks: translate_blocks(&job.blocks, &t.text),
+                text: t.text,
+                source_lang: t.detected_lang.or(pinned_lang),
+                target_lang: target_lang.clone(),
+            },
+            Err(err) => {
+                self.stats
+                    .record(&self.region_id, |stats| stats.request_errors += 1);
+                report_error(&self.app_handle, &self.region_id, &err.into());
+                TranslatedText {
+                    region: self.region_id.clone(),
+                    text: job.text.clone(),
+                    blocks: job.blocks.clone(),
+                    source_lang: pinned_lang,
+                    target_lang: target_lang.clone(),
+                }
+            }
+        };
+
+        Ok((translated, latency))
+    }
+
+    fn emit(&self, translated: TranslatedText, latency: StageLatency, captured_at: Instant) {
+        let started = Instant::now();
+        match lock(&self.last_translation, "last translation") {
+            Ok(mut last) => *last = Some(translated.text.clone()),
+            Err(err) => report_error(&self.app_handle, &self.region_id, &err),
         }
+        let label = overlay_label(&self.region_id);
+        if let Err(err) =
+            self.app_handle
+                .emit_to(label, Events::NewTranslatedText.as_str(), translated)
+        {
+            self.stats
+                .record(&self.region_id, |stats| stats.errors += 1);
+            report_error(&self.app_handle, &self.region_id, &err.into());
+            return;
+        }
+
+        self.stats.record_latency(
+            &self.region_id,
+            StageLatency {
+                emit_ms: millis(started.elapsed()),
+                total_ms: millis(captured_at.elapsed()),
+                ..latency
+            },
+        );
+    }
+}
+
+fn join_blocks(blocks: &[TextBlock]) -> String {
+    let mut text_buffer = String::from("");
+    for block in blocks {
+        text_buffer.push_str(format!("{}\n", block.text).as_str());
+    }
+
+    text_buffer
+}
+
+/// Lines are sent as one text to keep the translation context, then mapped back to their
+/// source rectangles. If the service merged or split lines, everything goes in one block.
+fn translate_blocks(blocks: &[TextBlock], translated: &str) -> Vec<TextBlock> {
+    let lines: Vec<&str> = translated.lines().collect();
+    if lines.len() == blocks.len() {
+        return blocks
+            .iter()
+            .zip(lines)
+            .map(|(block, line)| TextBlock {
+                text: line.to_string(),
+                ..block.clone()
+            })
+            .collect();
+    }
+
+    let Some(first) = blocks.first() else {
+        return Vec::new();
+    };
+    let (mut left, mut top) = (first.x, first.y);
+    let (mut right, mut bottom) = (first.x + first.w, first.y + first.h);
+    for block in blocks {
+        left = left.min(block.x);
+        top = top.min(block.y);
+        right = right.max(block.x + block.w);
+        bottom = bottom.max(block.y + block.h);
+    }
+
+    vec![TextBlock {
+        text: translated.to_string(),
+        x: left,
+        y: top,
+        w: right - left,
+        h: bottom - top,
+    }]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INTERVAL: Duration = Duration::from_millis(1000);
+
+    fn run(cadence: &mut Cadence, changes: &[bool]) -> Vec<u128> {
+        changes
+            .iter()
+            .map(|changed| cadence.next(INTERVAL, true, *changed).as_millis())
+            .collect()
+    }
+
+    #[test]
+    fn changing_frames_speed_up_to_a_quarter_of_the_interval() {
+        let mut cadence = Cadence::default();
+
+        assert_eq!(run(&mut cadence, &[true, true, true]), [500, 250, 250]);
+    }
+
+    #[test]
+    fn the_fastest_delay_never_goes_below_the_minimum_interval() {
+        let mut cadence = Cadence::default();
+        let short = Duration::from_millis(200);
+
+        let delays: Vec<Duration> = (0..3).map(|_| cadence.next(short, true, true)).collect();
+
+        assert_eq!(delays, [MIN_INTERVAL; 3]);
+    }
+
+    #[test]
+    fn static_frames_slow_down_to_the_maximum_factor() {
+        let mut cadence = Cadence::default();
+
+        let delays = run(&mut cadence, &[false; 7]);
+
+        assert_eq!(delays, [1500, 2250, 3375, 5062, 7593, 8000, 8000]);
+        assert_eq!(INTERVAL * ADAPTIVE_MAX_FACTOR, Duration::from_millis(8000));
+    }
+
+    #[test]
+    fn a_change_after_a_static_stretch_halves_the_delay() {
+        let mut cadence = Cadence::default();
+        run(&mut cadence, &[false; 6]);
+
+        assert_eq!(run(&mut cadence, &[true, true]), [4000, 2000]);
+    }
+
+    #[test]
+    fn fixed_cadence_uses_the_interval_and_resets_the_adaptive_delay() {
+        let mut cadence = Cadence::default();
+        run(&mut cadence, &[false; 3]);
+
+        let too_short = Duration::from_millis(10);
+
+        assert_eq!(cadence.next(INTERVAL, false, false), INTERVAL);
+        assert_eq!(cadence.next(too_short, false, true), MIN_INTERVAL);
+        assert_eq!(run(&mut cadence, &[false]), [1500]);
     }
 }
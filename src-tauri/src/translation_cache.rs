@@ -0,0 +1,270 @@
+// Copyright © 2025 Nantsa Montillet
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::config::TranslatorConfig;
+use crate::errors::lock;
+use crate::translate_runtime::TranslateRuntime;
+use crate::translators::Translation;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::time::sleep;
+
+const CACHE_FILE: &str = "translation_cache.json";
+const CACHE_CAPACITY: usize = 5000;
+/// Changes are written at this pace and when the app exits, never on the translation path.
+const CACHE_FLUSH_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    text: String,
+    detected_lang: Option<String>,
+    last_used: u64,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct CacheData {
+    clock: u64,
+    entries: HashMap<String, CacheEntry>,
+    /// Keys by `last_used`, the first one is the next to evict.
+    #[serde(skip)]
+    order: BTreeMap<u64, String>,
+}
+
+/// On-disk LRU of past translations, shared by every capture region.
+pub struct TranslationCache {
+    path: PathBuf,
+    data: CacheData,
+    /// Set when the entries changed since the last flush. Hits only move an entry in the
+    /// eviction order, which is kept in memory and saved with the next change.
+    dirty: bool,
+}
+
+impl TranslationCache {
+    pub fn load(app: &AppHandle) -> Self {
+        let path = app
+            .path()
+            .app_config_dir()
+            .expect("Could not get app config dir")
+            .join(CACHE_FILE);
+        Self::from_file(path)
+    }
+
+    fn from_file(path: PathBuf) -> Self {
+        let mut data = fs::read_to_string(&path)
+            .ok()
+            .and_then(|d| serde_json::from_str::<CacheData>(&d).ok())
+            .unwrap_or_default();
+        data.order = data
+            .entries
+            .iter()
+            .map(|(key, entry)| (entry.last_used, key.clone()))
+            .collect();
+
+        Self {
+            path,
+            data,
+            dirty: false,
+        }
+    }
+
+    pub fn key(
+        translator: &TranslatorConfig,
+        source_lang: &str,
+        target_lang: &str,
+        text: &str,
+    ) -> String {
+        format!(
+            "{}|{}|{}|{}",
+            translator.cache_id(),
+            source_lang,
+            target_lang,
+            normalize(text)
+        )
+    }
+
+    pub fn get(&mut self, key: &str) -> Option<Translation> {
+        self.data.clock += 1;
+        let clock = self.data.clock;
+        let entry = self.data.entries.get_mut(key)?;
+        self.data.order.remove(&entry.last_used);
+        self.data.order.insert(clock, key.to_string());
+        entry.last_used = clock;
+
+        Some(Translation {
+            text: entry.text.clone(),
+            detected_lang: entry.detected_lang.clone(),
+        })
+    }
+
+    pub fn insert(&mut self, key: String, translation: &Translation) {
+        self.data.clock += 1;
+        let entry = CacheEntry {
+            text: translation.text.clone(),
+            detected_lang: translation.detected_lang.clone(),
+            last_used: self.data.clock,
+        };
+        self.data.order.insert(self.data.clock, key.clone());
+        if let Some(old) = self.data.entries.insert(key, entry) {
+            self.data.order.remove(&old.last_used);
+        }
+
+        while self.data.entries.len() > CACHE_CAPACITY {
+            let Some((_, oldest)) = self.data.order.pop_first() else {
+                break;
+            };
+            self.data.entries.remove(&oldest);
+        }
+        self.dirty = true;
+    }
+
+    /// Writes the cache if it changed since the last flush. The lock is only held to serialise
+    /// it, not while writing the file.
+    pub fn flush(cache: &Mutex<TranslationCache>) {
+        let (path, data) = {
+            let mut cache = match lock(cache, "translation cache") {
+                Ok(cache) => cache,
+                Err(err) => {
+                    eprintln!("Could not flush translation cache: {}", err);
+                    return;
+                }
+            };
+            if !cache.dirty {
+                return;
+            }
+            cache.dirty = false;
+            (cache.path.clone(), serde_json::to_string(&cache.data))
+        };
+
+        match data {
+            Ok(data) => {
+                if let Err(err) = fs::write(&path, data) {
+                    eprintln!("Could not write translation cache: {}", err);
+                }
+            }
+            Err(err) => eprintln!("Could not serialize translation cache: {}", err),
+        }
+    }
+}
+
+/// Flushes the cache every `CACHE_FLUSH_INTERVAL`, off the async runtime.
+pub fn start_cache_flusher(app: &AppHandle) {
+    let cache = app.state::<TranslateRuntime>().cache();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            sleep(CACHE_FLUSH_INTERVAL).await;
+
+            let cache = cache.clone();
+            let flushed =
+                tauri::async_runtime::spawn_blocking(move || TranslationCache::flush(&cache));
+            if let Err(err) = flushed.await {
+                eprintln!("Could not flush translation cache: {}", err);
+            }
+        }
+    });
+}
+
+/// OCR output varies in spacing between frames, only the words matter for the cache.
+fn normalize(text: &str) -> String {
+    text.lines()
+        .map(|l| l.split_whitespace().collect::<Vec<&str>>().join(" "))
+        .filter(|l| !l.is_empty())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn empty_cache(name: &str) -> TranslationCache {
+        let path = std::env::temp_dir().join(format!(
+            "transcendia-cache-{}-{}.json",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+        TranslationCache::from_file(path)
+    }
+
+    fn translation(text: &str) -> Translation {
+        Translation {
+            text: text.to_string(),
+            detected_lang: None,
+        }
+    }
+
+    fn fill(cache: &mut TranslationCache, count: usize) {
+        for i in 0..count {
+            cache.insert(i.to_string(), &translation(&format!("text {}", i)));
+        }
+    }
+
+    #[test]
+    fn returns_inserted_translations() {
+        let mut cache = empty_cache("hit");
+        let key = TranslationCache::key(&TranslatorConfig::default(), "en", "fr", "Hello");
+        cache.insert(key, &translation("Bonjour"));
+
+        let spaced = TranslationCache::key(&TranslatorConfig::default(), "en", "fr", " Hello \n");
+        assert_eq!(cache.get(&spaced).unwrap().text, "Bonjour");
+        assert!(cache.get("missing").is_none());
+    }
+
+    #[test]
+    fn evicts_the_least_recently_used_entry() {
+        let mut cache = empty_cache("evict");
+        fill(&mut cache, CACHE_CAPACITY);
+        cache.get("0");
+
+        cache.insert("new".to_string(), &translation("new"));
+
+        assert_eq!(cache.data.entries.len(), CACHE_CAPACITY);
+        assert!(cache.get("0").is_some());
+        assert!(cache.get("1").is_none());
+        assert!(cache.get("new").is_some());
+    }
+
+    #[test]
+    fn replacing_an_entry_keeps_one_position() {
+        let mut cache = empty_cache("replace");
+        cache.insert("a".to_string(), &translation("first"));
+        cache.insert("a".to_string(), &translation("second"));
+
+        assert_eq!(cache.data.order.len(), 1);
+        assert_eq!(cache.get("a").unwrap().text, "second");
+        assert_eq!(cache.data.order.len(), 1);
+    }
+
+    #[test]
+    fn only_changes_need_a_flush() {
+        let mut cache = empty_cache("dirty");
+        cache.insert("a".to_string(), &translation("a"));
+        assert!(cache.dirty);
+        cache.dirty = false;
+
+        cache.get("a");
+
+        assert!(!cache.dirty);
+    }
+
+    #[test]
+    fn reloads_entries_in_their_order() {
+        let mut cache = empty_cache("reload");
+        fill(&mut cache, 3);
+        cache.get("0");
+        let path = cache.path.clone();
+        let cache = Mutex::new(cache);
+
+        TranslationCache::flush(&cache);
+        let reloaded = TranslationCache::from_file(path.clone());
+        let _ = fs::remove_file(path);
+
+        let order: Vec<&str> = reloaded.data.order.values().map(String::as_str).collect();
+        assert_eq!(order, ["1", "2", "0"]);
+    }
+}
@@ -0,0 +1,115 @@
+// Copyright © 2025 Nantsa Montillet
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::translators::{
+    trim_base_url, TranslateError, Translation, Translator, AUTO_DETECT_LANG,
+};
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::json;
+
+#[derive(Deserialize)]
+struct DeepLResponse {
+    translations: Vec<DeepLTranslation>,
+}
+
+#[derive(Deserialize)]
+struct DeepLTranslation {
+    text: String,
+    detected_source_language: Option<String>,
+}
+
+pub struct DeepLTranslator {
+    pub api_url: String,
+    api_key: String,
+}
+
+impl DeepLTranslator {
+    pub fn new(api_url: &str, api_key: &str) -> Self {
+        Self {
+            api_url: trim_base_url(api_url),
+            api_key: api_key.to_string(),
+        }
+    }
+}
+
+impl Translator for DeepLTranslator {
+    async fn translate(
+        &self,
+        client: &Client,
+        text: &str,
+        source_lang: &str,
+        target_lang: &str,
+    ) -> Result<Translation, TranslateError> {
+        let mut body = json!({
+            "text": [text],
+            "target_lang": target_lang.to_uppercase(),
+        });
+        if source_lang != AUTO_DETECT_LANG {
+            body["source_lang"] = json!(source_lang.to_uppercase());
+        }
+
+        let res = client
+            .post(format!("{}/v2/translate", self.api_url))
+            .header("Authorization", format!("DeepL-Auth-Key {}", self.api_key))
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<DeepLResponse>()
+            .await?;
+
+        res.translations
+            .into_iter()
+            .next()
+            .map(|t| Translation {
+                text: t.text,
+                detected_lang: t.detected_source_language.map(|l| l.to_lowercase()),
+            })
+            .ok_or_else(|| {
+                TranslateError::UnexpectedResponse("DeepL returned no translation".to_string())
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::translators::test_server::{block_on, serve_once};
+
+    #[test]
+    fn translates_with_auth_key() {
+        let (url, request) = serve_once(
+            200,
+            r#"{"translations":[{"text":"Bonjour","detected_source_language":"EN"}]}"#,
+        );
+        let translator = DeepLTranslator::new(&url, "secret");
+
+        let translation =
+            block_on(translator.translate(&Client::new(), "Hello", AUTO_DETECT_LANG, "fr"))
+                .unwrap();
+
+        assert_eq!(translation.text, "Bonjour");
+        assert_eq!(translation.detected_lang.as_deref(), Some("en"));
+        let request = request.join().unwrap();
+        assert!(request.line.starts_with("POST /v2/translate "));
+        assert_eq!(
+            request.header("Authorization"),
+            Some("DeepL-Auth-Key secret")
+        );
+        let body = request.json();
+        assert_eq!(body["text"], json!(["Hello"]));
+        assert_eq!(body["target_lang"], "FR");
+        assert!(body.get("source_lang").is_none());
+    }
+
+    #[test]
+    fn sends_source_lang_when_set() {
+        let (url, request) = serve_once(200, r#"{"translations":[{"text":"Bonjour"}]}"#);
+        let translator = DeepLTranslator::new(&url, "secret");
+
+        block_on(translator.translate(&Client::new(), "Hello", "en", "fr")).unwrap();
+
+        assert_eq!(request.join().unwrap().json()["source_lang"], "EN");
+    }
+}
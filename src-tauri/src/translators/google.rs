@@ -0,0 +1,100 @@
+// Copyright © 2025 Nantsa Montillet
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::translators::{trim_base_url, TranslateError, Translation, Translator};
+use reqwest::{Client, Url};
+use serde_json::Value;
+
+pub const GOOGLE_BASE_URL: &str = "https://translate.googleapis.com";
+
+pub struct GoogleTranslator {
+    pub base_url: String,
+}
+
+impl GoogleTranslator {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            base_url: trim_base_url(base_url),
+        }
+    }
+}
+
+impl Translator for GoogleTranslator {
+    async fn translate(
+        &self,
+        client: &Client,
+        text: &str,
+        source_lang: &str,
+        target_lang: &str,
+    ) -> Result<Translation, TranslateError> {
+        let mut url = Url::parse(format!("{}/translate_a/single", self.base_url).as_str())
+            .map_err(|err| TranslateError::UnexpectedResponse(err.to_string()))?;
+        url.query_pairs_mut()
+            .append_pair("client", "gtx")
+            .append_pair("sl", source_lang)
+            .append_pair("tl", target_lang)
+            .append_pair("dt", "t")
+            .append_pair("q", text);
+
+        let json = client
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Value>()
+            .await?;
+
+        let text = json
+            .get(0)
+            .and_then(|v| v.as_array())
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|i| i.get(0).and_then(|t| t.as_str()))
+                    .collect::<String>()
+            })
+            .ok_or_else(|| {
+                TranslateError::UnexpectedResponse(
+                    "Could not find translated text in response".to_string(),
+                )
+            })?;
+
+        Ok(Translation {
+            text,
+            detected_lang: json.get(2).and_then(|l| l.as_str()).map(String::from),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::translators::test_server::{block_on, serve_once};
+
+    #[test]
+    fn translates_with_gtx_endpoint() {
+        let (url, request) = serve_once(200, r#"[[["Bonjour","Hello",null,null,1]],null,"en"]"#);
+        let translator = GoogleTranslator::new(&url);
+
+        let translation =
+            block_on(translator.translate(&Client::new(), "Hello", "auto", "fr")).unwrap();
+
+        assert_eq!(translation.text, "Bonjour");
+        assert_eq!(translation.detected_lang.as_deref(), Some("en"));
+        let request = request.join().unwrap();
+        assert!(request.line.starts_with("GET /translate_a/single?"));
+        assert!(request.line.contains("sl=auto"));
+        assert!(request.line.contains("tl=fr"));
+        assert!(request.line.contains("q=Hello"));
+    }
+
+    #[test]
+    fn reports_http_errors() {
+        let (url, request) = serve_once(500, "{}");
+        let translator = GoogleTranslator::new(&url);
+
+        let result = block_on(translator.translate(&Client::new(), "Hello", "auto", "fr"));
+
+        assert!(matches!(result, Err(TranslateError::Request(_))));
+        request.join().unwrap();
+    }
+}
@@ -0,0 +1,105 @@
+// Copyright © 2025 Nantsa Montillet
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::translators::{trim_base_url, TranslateError, Translation, Translator};
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::json;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LibreTranslateResponse {
+    translated_text: String,
+    detected_language: Option<DetectedLanguage>,
+}
+
+#[derive(Deserialize)]
+struct DetectedLanguage {
+    language: String,
+}
+
+pub struct LibreTranslator {
+    pub url: String,
+    api_key: Option<String>,
+}
+
+impl LibreTranslator {
+    pub fn new(url: &str, api_key: Option<String>) -> Self {
+        Self {
+            url: trim_base_url(url),
+            api_key: api_key.filter(|k| !k.is_empty()),
+        }
+    }
+}
+
+impl Translator for LibreTranslator {
+    async fn translate(
+        &self,
+        client: &Client,
+        text: &str,
+        source_lang: &str,
+        target_lang: &str,
+    ) -> Result<Translation, TranslateError> {
+        let mut body = json!({
+            "q": text,
+            "source": source_lang,
+            "target": target_lang,
+            "format": "text",
+        });
+        if let Some(key) = &self.api_key {
+            body["api_key"] = json!(key);
+        }
+
+        let res = client
+            .post(format!("{}/translate", self.url))
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<LibreTranslateResponse>()
+            .await?;
+
+        Ok(Translation {
+            text: res.translated_text,
+            detected_lang: res.detected_language.map(|l| l.language),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::translators::test_server::{block_on, serve_once};
+
+    #[test]
+    fn translates_with_api_key() {
+        let (url, request) = serve_once(
+            200,
+            r#"{"translatedText":"Bonjour","detectedLanguage":{"confidence":90,"language":"en"}}"#,
+        );
+        let translator = LibreTranslator::new(&url, Some("secret".to_string()));
+
+        let translation =
+            block_on(translator.translate(&Client::new(), "Hello", "auto", "fr")).unwrap();
+
+        assert_eq!(translation.text, "Bonjour");
+        assert_eq!(translation.detected_lang.as_deref(), Some("en"));
+        let request = request.join().unwrap();
+        assert!(request.line.starts_with("POST /translate "));
+        let body = request.json();
+        assert_eq!(body["q"], "Hello");
+        assert_eq!(body["source"], "auto");
+        assert_eq!(body["target"], "fr");
+        assert_eq!(body["api_key"], "secret");
+    }
+
+    #[test]
+    fn omits_empty_api_key() {
+        let (url, request) = serve_once(200, r#"{"translatedText":"Bonjour"}"#);
+        let translator = LibreTranslator::new(&url, Some(String::new()));
+
+        block_on(translator.translate(&Client::new(), "Hello", "en", "fr")).unwrap();
+
+        assert!(request.join().unwrap().json().get("api_key").is_none());
+    }
+}
@@ -0,0 +1,156 @@
+// Copyright © 2025 Nantsa Montillet
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+mod deepl;
+mod google;
+mod libre_translate;
+mod offline;
+mod openai;
+#[cfg(test)]
+mod test_server;
+mod unigram;
+
+use crate::config::TranslatorConfig;
+use crate::errors::TranscendiaError;
+use crate::ocr_models::translation_model_dir;
+use reqwest::Client;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use std::time::Duration;
+
+pub use deepl::DeepLTranslator;
+pub use google::{GoogleTranslator, GOOGLE_BASE_URL};
+pub use libre_translate::LibreTranslator;
+pub use offline::{
+    OfflineTranslator, DECODER_MODEL_FILE, ENCODER_MODEL_FILE, MODEL_CONFIG_FILE, TOKENIZER_FILE,
+};
+pub use openai::OpenAiTranslator;
+
+#[derive(Debug)]
+pub enum TranslateError {
+    Request(reqwest::Error),
+    UnexpectedResponse(String),
+    Offline(String),
+}
+
+impl Display for TranslateError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TranslateError::Request(err) => write!(f, "Translation request failed: {}", err),
+            TranslateError::UnexpectedResponse(msg) => {
+                write!(f, "Unexpected translation response: {}", msg)
+            }
+            TranslateError::Offline(msg) => write!(f, "Offline translation failed: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for TranslateError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TranslateError::Request(err) => Some(err),
+            TranslateError::UnexpectedResponse(_) | TranslateError::Offline(_) => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for TranslateError {
+    fn from(err: reqwest::Error) -> Self {
+        TranslateError::Request(err)
+    }
+}
+
+pub const AUTO_DETECT_LANG: &str = "auto";
+
+pub struct Translation {
+    pub text: String,
+    pub detected_lang: Option<String>,
+}
+
+pub trait Translator {
+    /// `source_lang` is `AUTO_DETECT_LANG` when the service should detect it by itself.
+    async fn translate(
+        &self,
+        client: &Client,
+        text: &str,
+        source_lang: &str,
+        target_lang: &str,
+    ) -> Result<Translation, TranslateError>;
+}
+
+/// Every translation service Transcendia knows about, built from the user `Config`.
+pub enum Backend {
+    Google(GoogleTranslator),
+    DeepL(DeepLTranslator),
+    LibreTranslate(LibreTranslator),
+    OpenAi(OpenAiTranslator),
+    Offline(OfflineTranslator),
+}
+
+impl Backend {
+    pub fn from_config(
+        config: &TranslatorConfig,
+        models_folder: &Path,
+    ) -> Result<Self, TranscendiaError> {
+        Ok(match config {
+            TranslatorConfig::Google { url } => Backend::Google(GoogleTranslator::new(url)),
+            TranslatorConfig::DeepL { api_key, api_url } => {
+                Backend::DeepL(DeepLTranslator::new(api_url, api_key))
+            }
+            TranslatorConfig::LibreTranslate { url, api_key } => {
+                Backend::LibreTranslate(LibreTranslator::new(url, api_key.clone()))
+            }
+            TranslatorConfig::OpenAi {
+                url,
+                api_key,
+                model,
+            } => Backend::OpenAi(OpenAiTranslator::new(url, api_key.clone(), model)),
+            TranslatorConfig::Offline { model } => Backend::Offline(OfflineTranslator::new(
+                model,
+                translation_model_dir(models_folder, model)?,
+            )),
+        })
+    }
+
+    fn endpoint(&self) -> &str {
+        match self {
+            Backend::Google(t) => &t.base_url,
+            Backend::DeepL(t) => &t.api_url,
+            Backend::LibreTranslate(t) => &t.url,
+            Backend::OpenAi(t) => &t.url,
+            Backend::Offline(_) => "",
+        }
+    }
+
+    /// Self-hosted services often live on plain http inside a LAN, so https is only
+    /// enforced when the configured endpoint itself uses it.
+    pub fn build_client(&self) -> reqwest::Result<Client> {
+        Client::builder()
+            .connect_timeout(Duration::from_secs(10))
+            .timeout(Duration::from_secs(20))
+            .https_only(!self.endpoint().starts_with("http://"))
+            .build()
+    }
+}
+
+impl Translator for Backend {
+    async fn translate(
+        &self,
+        client: &Client,
+        text: &str,
+        source_lang: &str,
+        target_lang: &str,
+    ) -> Result<Translation, TranslateError> {
+        match self {
+            Backend::Google(t) => t.translate(client, text, source_lang, target_lang).await,
+            Backend::DeepL(t) => t.translate(client, text, source_lang, target_lang).await,
+            Backend::LibreTranslate(t) => t.translate(client, text, source_lang, target_lang).await,
+            Backend::OpenAi(t) => t.translate(client, text, source_lang, target_lang).await,
+            Backend::Offline(t) => t.translate(client, text, source_lang, target_lang).await,
+        }
+    }
+}
+
+fn trim_base_url(url: &str) -> String {
+    url.trim_end_matches('/').to_string()
+}
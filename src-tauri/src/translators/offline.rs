@@ -0,0 +1,272 @@
+// Copyright © 2025 Nantsa Montillet
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::translators::unigram::UnigramTokenizer;
+use crate::translators::{TranslateError, Translation, Translator, AUTO_DETECT_LANG};
+use reqwest::Client;
+use rten::Model;
+use rten_tensor::prelude::*;
+use rten_tensor::NdTensor;
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+
+/// rten only reads its own format, the ONNX exports are converted with `rten-convert`.
+pub const ENCODER_MODEL_FILE: &str = "encoder_model.rten";
+pub const DECODER_MODEL_FILE: &str = "decoder_model.rten";
+pub const TOKENIZER_FILE: &str = "tokenizer.json";
+pub const MODEL_CONFIG_FILE: &str = "config.json";
+
+const MAX_OUTPUT_TOKENS: usize = 256;
+/// Source language of the models that read many languages, such as `mul-en`.
+const ANY_LANGUAGE: &str = "mul";
+
+#[derive(Deserialize)]
+struct MarianConfig {
+    decoder_start_token_id: u32,
+    eos_token_id: u32,
+    pad_token_id: u32,
+}
+
+struct MarianModel {
+    encoder: Model,
+    decoder: Model,
+    tokenizer: UnigramTokenizer,
+    config: MarianConfig,
+}
+
+impl MarianModel {
+    fn load(dir: &Path) -> Result<Self, TranslateError> {
+        let config = fs::read_to_string(dir.join(MODEL_CONFIG_FILE))
+            .map_err(offline_error)
+            .and_then(|c| serde_json::from_str::<MarianConfig>(&c).map_err(offline_error))?;
+
+        Ok(Self {
+            encoder: Model::load_file(dir.join(ENCODER_MODEL_FILE)).map_err(offline_error)?,
+            decoder: Model::load_file(dir.join(DECODER_MODEL_FILE)).map_err(offline_error)?,
+            tokenizer: UnigramTokenizer::from_file(&dir.join(TOKENIZER_FILE))
+                .map_err(offline_error)?,
+            config,
+        })
+    }
+
+    fn translate_line(&self, text: &str) -> Result<String, TranslateError> {
+        // Marian expects the source sentence to end with the end of sentence token.
+        let source_ids: Vec<i32> = self
+            .tokenizer
+            .encode(text)
+            .into_iter()
+            .chain([self.config.eos_token_id])
+            .map(|id| id as i32)
+            .collect();
+        let input_ids = NdTensor::from_data([1, source_ids.len()], source_ids);
+        let attention_mask = NdTensor::full([1, input_ids.size(1)], 1i32);
+
+        let encoder_input_ids = self.encoder.node_id("input_ids").map_err(offline_error)?;
+        let encoder_mask = self
+            .encoder
+            .node_id("attention_mask")
+            .map_err(offline_error)?;
+        let encoder_output = self
+            .encoder
+            .node_id("last_hidden_state")
+            .map_err(offline_error)?;
+        let hidden_states: NdTensor<f32, 3> = self
+            .encoder
+            .run(
+                vec![
+                    (encoder_input_ids, input_ids.view().into()),
+                    (encoder_mask, attention_mask.view().into()),
+                ],
+                &[encoder_output],
+                None,
+            )
+            .map_err(offline_error)?
+            .remove(0)
+            .try_into()
+            .map_err(offline_error)?;
+
+        let decoder_input_ids = self.decoder.node_id("input_ids").map_err(offline_error)?;
+        let decoder_hidden = self
+            .decoder
+            .node_id("encoder_hidden_states")
+            .map_err(offline_error)?;
+        let decoder_mask = self
+            .decoder
+            .node_id("encoder_attention_mask")
+            .map_err(offline_error)?;
+        let decoder_output = self.decoder.node_id("logits").map_err(offline_error)?;
+
+        // Greedy decoding, the whole prefix is fed again at each step as the exported
+        // decoder has no past key/values inputs.
+        let mut tokens = vec![self.config.decoder_start_token_id];
+        while tokens.len() <= MAX_OUTPUT_TOKENS {
+            let ids = NdTensor::from_data(
+                [1, tokens.len()],
+                tokens.iter().map(|&t| t as i32).collect::<Vec<i32>>(),
+            );
+            let logits: NdTensor<f32, 3> = self
+                .decoder
+                .run(
+                    vec![
+                        (decoder_input_ids, ids.view().into()),
+                        (decoder_hidden, hidden_states.view().into()),
+                        (decoder_mask, attention_mask.view().into()),
+                    ],
+                    &[decoder_output],
+                    None,
+                )
+                .map_err(offline_error)?
+                .remove(0)
+                .try_into()
+                .map_err(offline_error)?;
+
+            let last = logits.size(1) - 1;
+            let next = (0..logits.size(2))
+                .filter(|&t| t as u32 != self.config.pad_token_id)
+                .max_by(|&a, &b| logits[[0, last, a]].total_cmp(&logits[[0, last, b]]))
+                .map(|t| t as u32)
+                .unwrap_or(self.config.eos_token_id);
+
+            if next == self.config.eos_token_id {
+                break;
+            }
+            tokens.push(next);
+        }
+
+        Ok(self.tokenizer.decode(
+            &tokens[1..],
+            &[self.config.eos_token_id, self.config.pad_token_id],
+        ))
+    }
+}
+
+/// Marian/OPUS-MT model run locally with `rten`, one model directory per language pair.
+pub struct OfflineTranslator {
+    pub model_dir: PathBuf,
+    model: OnceLock<Arc<MarianModel>>,
+    source_lang: String,
+    target_lang: String,
+}
+
+impl OfflineTranslator {
+    /// `pair` ends with the source and target languages of the model, such as `en-fr`.
+    pub fn new(pair: &str, model_dir: PathBuf) -> Self {
+        let mut langs = pair.rsplit('-').map(str::to_lowercase);
+        let target_lang = langs.next().unwrap_or_default();
+        let source_lang = langs.next().unwrap_or_default();
+        Self {
+            model_dir,
+            model: OnceLock::new(),
+            source_lang,
+            target_lang,
+        }
+    }
+
+    /// A model only translates its own pair, anything else would come out in the wrong language
+    /// under the region's language label.
+    fn check_languages(&self, source_lang: &str, target_lang: &str) -> Result<(), TranslateError> {
+        let source_ok = source_lang == AUTO_DETECT_LANG
+            || self.source_lang == ANY_LANGUAGE
+            || primary_language(source_lang) == self.source_lang;
+        if source_ok && primary_language(target_lang) == self.target_lang {
+            return Ok(());
+        }
+        Err(TranslateError::Offline(format!(
+            "the offline model translates {} to {}, not {} to {}",
+            self.source_lang, self.target_lang, source_lang, target_lang
+        )))
+    }
+
+    /// Loads the model on first use, reading hundreds of megabytes is kept off the async
+    /// runtime.
+    async fn model(&self) -> Result<Arc<MarianModel>, TranslateError> {
+        if let Some(model) = self.model.get() {
+            return Ok(model.clone());
+        }
+
+        let model_dir = self.model_dir.clone();
+        let model = tauri::async_runtime::spawn_blocking(move || MarianModel::load(&model_dir))
+            .await
+            .map_err(offline_error)??;
+        Ok(self.model.get_or_init(|| Arc::new(model)).clone())
+    }
+}
+
+impl Translator for OfflineTranslator {
+    async fn translate(
+        &self,
+        _client: &Client,
+        text: &str,
+        source_lang: &str,
+        target_lang: &str,
+    ) -> Result<Translation, TranslateError> {
+        self.check_languages(source_lang, target_lang)?;
+        let model = self.model().await?;
+        let text = text.to_string();
+
+        tauri::async_runtime::spawn_blocking(move || {
+            text.lines()
+                .map(|line| {
+                    if line.trim().is_empty() {
+                        Ok(String::new())
+                    } else {
+                        model.translate_line(line)
+                    }
+                })
+                .collect::<Result<Vec<String>, TranslateError>>()
+                .map(|lines| Translation {
+                    text: lines.join("\n"),
+                    detected_lang: None,
+                })
+        })
+        .await
+        .map_err(offline_error)?
+    }
+}
+
+/// `pt` for `pt-br`, models are named after the language only.
+fn primary_language(lang: &str) -> String {
+    lang.split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase()
+}
+
+fn offline_error(err: impl std::fmt::Display) -> TranslateError {
+    TranslateError::Offline(err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_the_languages_of_the_pair() {
+        let translator = OfflineTranslator::new("en-fr", PathBuf::new());
+
+        assert!(translator.check_languages("en", "fr").is_ok());
+        assert!(translator.check_languages(AUTO_DETECT_LANG, "fr").is_ok());
+    }
+
+    #[test]
+    fn multilingual_models_read_any_source() {
+        let translator = OfflineTranslator::new("mul-en", PathBuf::new());
+
+        assert!(translator.check_languages("ja", "en").is_ok());
+        assert!(translator.check_languages("pt-br", "en").is_ok());
+    }
+
+    #[test]
+    fn rejects_other_languages() {
+        let translator = OfflineTranslator::new("mul-en", PathBuf::new());
+
+        assert!(matches!(
+            translator.check_languages("ja", "fr"),
+            Err(TranslateError::Offline(_))
+        ));
+        let translator = OfflineTranslator::new("en-fr", PathBuf::new());
+        assert!(translator.check_languages("de", "fr").is_err());
+    }
+}
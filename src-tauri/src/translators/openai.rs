@@ -0,0 +1,134 @@
+// Copyright © 2025 Nantsa Montillet
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::translators::{
+    trim_base_url, TranslateError, Translation, Translator, AUTO_DETECT_LANG,
+};
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::json;
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    choices: Vec<ChatChoice>,
+}
+
+#[derive(Deserialize)]
+struct ChatChoice {
+    message: ChatMessage,
+}
+
+#[derive(Deserialize)]
+struct ChatMessage {
+    content: String,
+}
+
+/// Any server exposing the OpenAI `chat/completions` API (OpenAI, Ollama, llama.cpp, vLLM...).
+pub struct OpenAiTranslator {
+    pub url: String,
+    api_key: Option<String>,
+    model: String,
+}
+
+impl OpenAiTranslator {
+    pub fn new(url: &str, api_key: Option<String>, model: &str) -> Self {
+        Self {
+            url: trim_base_url(url),
+            api_key: api_key.filter(|k| !k.is_empty()),
+            model: model.to_string(),
+        }
+    }
+}
+
+impl Translator for OpenAiTranslator {
+    async fn translate(
+        &self,
+        client: &Client,
+        text: &str,
+        source_lang: &str,
+        target_lang: &str,
+    ) -> Result<Translation, TranslateError> {
+        let source = if source_lang == AUTO_DETECT_LANG {
+            String::new()
+        } else {
+            format!(" from the language with ISO code '{}'", source_lang)
+        };
+
+        let mut req = client
+            .post(format!("{}/chat/completions", self.url))
+            .json(&json!({
+                "model": self.model,
+                "temperature": 0,
+                "messages": [
+                    {
+                        "role": "system",
+                        "content": format!(
+                            "Translate the user text{} to the language with ISO code '{}'. \
+                            Keep the line breaks and answer with the translation only.",
+                            source, target_lang
+                        ),
+                    },
+                    { "role": "user", "content": text },
+                ],
+            }));
+        if let Some(key) = &self.api_key {
+            req = req.bearer_auth(key);
+        }
+
+        let res = req
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<ChatResponse>()
+            .await?;
+
+        res.choices
+            .into_iter()
+            .next()
+            .map(|c| Translation {
+                text: c.message.content.trim().to_string(),
+                detected_lang: None,
+            })
+            .ok_or_else(|| {
+                TranslateError::UnexpectedResponse("Chat endpoint returned no choice".to_string())
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::translators::test_server::{block_on, serve_once};
+
+    #[test]
+    fn translates_with_chat_completions() {
+        let (url, request) = serve_once(
+            200,
+            r#"{"choices":[{"message":{"role":"assistant","content":" Bonjour\n"}}]}"#,
+        );
+        let translator = OpenAiTranslator::new(&url, Some("secret".to_string()), "local-model");
+
+        let translation =
+            block_on(translator.translate(&Client::new(), "Hello", "en", "fr")).unwrap();
+
+        assert_eq!(translation.text, "Bonjour");
+        assert_eq!(translation.detected_lang, None);
+        let request = request.join().unwrap();
+        assert!(request.line.starts_with("POST /chat/completions "));
+        assert_eq!(request.header("Authorization"), Some("Bearer secret"));
+        let body = request.json();
+        assert_eq!(body["model"], "local-model");
+        assert_eq!(body["messages"][1]["content"], "Hello");
+    }
+
+    #[test]
+    fn fails_without_choices() {
+        let (url, request) = serve_once(200, r#"{"choices":[]}"#);
+        let translator = OpenAiTranslator::new(&url, None, "local-model");
+
+        let result = block_on(translator.translate(&Client::new(), "Hello", "en", "fr"));
+
+        assert!(matches!(result, Err(TranslateError::UnexpectedResponse(_))));
+        assert!(request.join().unwrap().header("Authorization").is_none());
+    }
+}
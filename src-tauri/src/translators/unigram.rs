@@ -0,0 +1,186 @@
+// Copyright © 2025 Nantsa Montillet
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! SentencePiece Unigram tokenizer read from a Hugging Face `tokenizer.json`, the format of the
+//! Marian/OPUS-MT tokenizers. rten-text only implements BPE and WordPiece.
+
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Marks the start of a word, the Metaspace pre-tokenizer replaces spaces with it.
+const DEFAULT_REPLACEMENT: char = '▁';
+/// Score of a character missing from the vocabulary, below any real piece.
+const UNKNOWN_PENALTY: f32 = 10.0;
+
+#[derive(Deserialize)]
+struct TokenizerJson {
+    pre_tokenizer: Option<MetaspaceJson>,
+    model: UnigramJson,
+}
+
+#[derive(Deserialize)]
+struct MetaspaceJson {
+    replacement: Option<char>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type")]
+enum UnigramJson {
+    Unigram {
+        vocab: Vec<(String, f32)>,
+        unk_id: Option<u32>,
+    },
+}
+
+pub struct UnigramTokenizer {
+    /// Piece text to id and log probability.
+    pieces: HashMap<String, (u32, f32)>,
+    /// Piece text by id.
+    vocab: Vec<String>,
+    unk_id: u32,
+    unk_score: f32,
+    /// Length in chars of the longest piece, bounds the search.
+    max_piece_len: usize,
+    replacement: char,
+}
+
+impl UnigramTokenizer {
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let json = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        Self::from_json(&json)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let TokenizerJson {
+            pre_tokenizer,
+            model: UnigramJson::Unigram { vocab, unk_id },
+        } = serde_json::from_str(json).map_err(|e| e.to_string())?;
+
+        let unk_id = unk_id.ok_or("the tokenizer has no unknown token")?;
+        let min_score = vocab.iter().map(|(_, score)| *score).fold(0.0, f32::min);
+        let pieces = vocab
+            .iter()
+            .enumerate()
+            .map(|(id, (piece, score))| (piece.clone(), (id as u32, *score)))
+            .collect();
+
+        Ok(Self {
+            pieces,
+            max_piece_len: vocab
+                .iter()
+                .map(|(piece, _)| piece.chars().count())
+                .max()
+                .unwrap_or(1),
+            vocab: vocab.into_iter().map(|(piece, _)| piece).collect(),
+            unk_id,
+            unk_score: min_score - UNKNOWN_PENALTY,
+            replacement: pre_tokenizer
+                .and_then(|p| p.replacement)
+                .unwrap_or(DEFAULT_REPLACEMENT),
+        })
+    }
+
+    pub fn encode(&self, text: &str) -> Vec<u32> {
+        let mut ids = Vec::new();
+        for word in text.split_whitespace() {
+            let chars: Vec<char> = std::iter::once(self.replacement)
+                .chain(word.chars())
+                .collect();
+            self.encode_word(&chars, &mut ids);
+        }
+        ids
+    }
+
+    /// Viterbi search of the most likely split of one word into pieces.
+    fn encode_word(&self, chars: &[char], ids: &mut Vec<u32>) {
+        // Best score to reach each position, with the start and id of the last piece.
+        let mut best: Vec<(f32, usize, Option<u32>)> =
+            vec![(f32::NEG_INFINITY, 0, None); chars.len() + 1];
+        best[0].0 = 0.0;
+
+        for end in 1..=chars.len() {
+            for start in end.saturating_sub(self.max_piece_len)..end {
+                if best[start].0 == f32::NEG_INFINITY {
+                    continue;
+                }
+                let piece: String = chars[start..end].iter().collect();
+                if let Some(&(id, score)) = self.pieces.get(&piece) {
+                    let total = best[start].0 + score;
+                    if total > best[end].0 {
+                        best[end] = (total, start, Some(id));
+                    }
+                }
+            }
+            let unknown = best[end - 1].0 + self.unk_score;
+            if unknown > best[end].0 {
+                best[end] = (unknown, end - 1, None);
+            }
+        }
+
+        let mut path = Vec::new();
+        let mut end = chars.len();
+        while end > 0 {
+            let (_, start, id) = best[end];
+            path.push(id.unwrap_or(self.unk_id));
+            end = start;
+        }
+        // Consecutive unknown characters are a single unknown token, as in SentencePiece.
+        for id in path.into_iter().rev() {
+            if id != self.unk_id || ids.last() != Some(&self.unk_id) {
+                ids.push(id);
+            }
+        }
+    }
+
+    /// Text of `ids`, the tokens in `skip` (end of sentence, padding...) left out.
+    pub fn decode(&self, ids: &[u32], skip: &[u32]) -> String {
+        ids.iter()
+            .filter(|&id| !skip.contains(id))
+            .filter_map(|&id| self.vocab.get(id as usize))
+            .map(String::as_str)
+            .collect::<String>()
+            .replace(self.replacement, " ")
+            .trim()
+            .to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOKENIZER: &str = r#"{
+        "pre_tokenizer": {"type": "Metaspace", "replacement": "▁", "add_prefix_space": true},
+        "model": {
+            "type": "Unigram",
+            "unk_id": 1,
+            "vocab": [
+                ["</s>", 0.0], ["<unk>", 0.0], ["▁", -2.0], ["▁hello", -3.0],
+                ["▁hel", -4.0], ["lo", -4.0], ["▁world", -3.0], ["s", -5.0], ["<pad>", 0.0]
+            ]
+        }
+    }"#;
+
+    #[test]
+    fn prefers_the_most_likely_split() {
+        let tokenizer = UnigramTokenizer::from_json(TOKENIZER).unwrap();
+
+        assert_eq!(tokenizer.encode("hello  worlds"), vec![3, 6, 7]);
+    }
+
+    #[test]
+    fn merges_unknown_characters() {
+        let tokenizer = UnigramTokenizer::from_json(TOKENIZER).unwrap();
+
+        assert_eq!(tokenizer.encode("hello ??"), vec![3, 2, 1]);
+    }
+
+    #[test]
+    fn decodes_without_special_tokens() {
+        let tokenizer = UnigramTokenizer::from_json(TOKENIZER).unwrap();
+
+        assert_eq!(tokenizer.decode(&[8, 3, 6, 7, 0], &[0, 8]), "hello worlds");
+    }
+}
@@ -1,18 +1,23 @@
 // Copyright © 2025 Nantsa Montillet
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
+use crate::capture::find_monitor;
 use crate::config::Region;
+use crate::errors::TranscendiaError;
 use tauri::utils::config::WindowEffectsConfig;
 use tauri::utils::{WindowEffect, WindowEffectState};
 use tauri::webview::Color;
 use tauri::{
-    AppHandle, LogicalPosition, LogicalSize, WebviewUrl, WebviewWindow, WebviewWindowBuilder,
+    AppHandle, LogicalPosition, LogicalSize, Manager, WebviewUrl, WebviewWindow,
+    WebviewWindowBuilder,
 };
 
+const OVERLAY_LABEL_PREFIX: &str = "overlay-";
+
 pub fn create_select_region_window(
     app: &AppHandle,
     monitor: u32,
-) -> Result<WebviewWindow, tauri::Error> {
+) -> Result<WebviewWindow, TranscendiaError> {
     let window =
         WebviewWindowBuilder::new(app, "select", WebviewUrl::App("select.html".into()))
             .title("Transcendia - Select a region")
@@ -28,21 +33,17 @@ pub fn create_select_region_window(
             .visible(false)
             .build()?;
 
-    let monitors = xcap::Monitor::all().expect("Could not retrieve monitors");
-    let monitor = monitors
-        .iter()
-        .find(|m| m.id().unwrap() == monitor)
-        .unwrap_or(monitors.get(0).expect("Cannot find any monitor"));
-    let scale = monitor.scale_factor().unwrap();
-    window.set_position(LogicalPosition { x: monitor.x().unwrap() as f32 * scale, y: monitor.y().unwrap() as f32 * scale })?;
-    window.set_size(LogicalSize { width: monitor.width().unwrap() as f32, height: monitor.height().unwrap() as f32 })?;
+    let monitor = find_monitor(monitor)?;
+    let scale = monitor.scale_factor()?;
+    window.set_position(LogicalPosition { x: monitor.x()? as f32 * scale, y: monitor.y()? as f32 * scale })?;
+    window.set_size(LogicalSize { width: monitor.width()? as f32, height: monitor.height()? as f32 })?;
     window.show()?;
     window.set_focus()?;
 
     Ok(window)
 }
 
-pub fn create_config_window(app: &AppHandle) -> Result<WebviewWindow, tauri::Error> {
+pub fn create_config_window(app: &AppHandle) -> Result<WebviewWindow, TranscendiaError> {
     let window = WebviewWindowBuilder::new(app, "config", WebviewUrl::App("config.html".into()))
         .title("Transcendia - Configuration")
         .accept_first_mouse(true)
@@ -55,7 +56,7 @@ pub fn create_config_window(app: &AppHandle) -> Result<WebviewWindow, tauri::Err
     Ok(window)
 }
 
-pub fn create_download_window(app: &AppHandle) -> Result<WebviewWindow, tauri::Error> {
+pub fn create_download_window(app: &AppHandle) -> Result<WebviewWindow, TranscendiaError> {
     let window =
         WebviewWindowBuilder::new(app, "downloader", WebviewUrl::App("models.html".into()))
             .title("Transcendia - Downloader")
@@ -69,13 +70,38 @@ pub fn create_download_window(app: &AppHandle) -> Result<WebviewWindow, tauri::E
     Ok(window)
 }
 
+pub fn overlay_label(region_id: &str) -> String {
+    format!("{}{}", OVERLAY_LABEL_PREFIX, region_id)
+}
+
+/// Hides every overlay if one is visible, shows them all otherwise.
+pub fn toggle_overlays(app: &AppHandle) -> Result<(), TranscendiaError> {
+    let overlays: Vec<WebviewWindow> = app
+        .webview_windows()
+        .into_values()
+        .filter(|w| w.label().starts_with(OVERLAY_LABEL_PREFIX))
+        .collect();
+    let any_visible = overlays.iter().any(|w| w.is_visible().unwrap_or(false));
+    for overlay in overlays {
+        if any_visible {
+            overlay.hide()?;
+        } else {
+            overlay.show()?;
+        }
+    }
+
+    Ok(())
+}
+
 pub fn create_overlay_window(
     app: &AppHandle,
+    region_id: &str,
     region: &Region,
-    monitor: u32,
+    origin: (f32, f32),
     blur: bool,
-) -> Result<WebviewWindow, tauri::Error> {
-    let window = WebviewWindowBuilder::new(app, "overlay", WebviewUrl::App("overlay.html".into()))
+) -> Result<WebviewWindow, TranscendiaError> {
+    let label = overlay_label(region_id);
+    let window = WebviewWindowBuilder::new(app, label, WebviewUrl::App("overlay.html".into()))
         .title("Transcendia - Overlay")
         .always_on_top(true)
         .visible_on_all_workspaces(true)
@@ -89,26 +115,23 @@ pub fn create_overlay_window(
         .build()?;
     window.set_ignore_cursor_events(true)?;
 
-    edit_overlay(&window, &region, monitor, blur)?;
+    edit_overlay(&window, region, origin, blur)?;
     window.show()?;
 
     Ok(window)
 }
 
+/// Places the overlay over a region relative to `origin`, see `capture::region_origin`.
+/// Effects are only applied when `blur` is set, moving an overlay can skip them.
 pub fn edit_overlay(
     window: &WebviewWindow,
     region: &Region,
-    monitor: u32,
+    origin: (f32, f32),
     blur: bool,
-) -> Result<(), tauri::Error> {
-    let monitors = xcap::Monitor::all().expect("Could not retrieve monitors");
-    let monitor = monitors
-        .iter()
-        .find(|m| m.id().unwrap() == monitor)
-        .unwrap_or(monitors.get(0).expect("Cannot find any monitor"));
+) -> Result<(), TranscendiaError> {
     window.set_position(LogicalPosition {
-        x: monitor.x().unwrap() as f32 * monitor.scale_factor().unwrap() + region.x as f32,
-        y: monitor.y().unwrap() as f32 * monitor.scale_factor().unwrap() + region.y as f32,
+        x: origin.0 + region.x as f32,
+        y: origin.1 + region.y as f32,
     })?;
     window.set_size(LogicalSize {
         width: region.w,
@@ -0,0 +1,27 @@
+/* Copyright © 2025 Xylobyte
+ * SPDX-License-Identifier: AGPL-3.0-or-later */
+
+import {TranslatorConfig} from "../types/config.ts";
+
+export const translators = [
+    {value: 'google', label: 'Google Translate'},
+    {value: 'deepl', label: 'DeepL'},
+    {value: 'libretranslate', label: 'LibreTranslate'},
+    {value: 'openai', label: 'OpenAI compatible'},
+    {value: 'offline', label: 'Offline (OPUS-MT)'},
+];
+
+export const defaultTranslatorConfig = (kind: string, apiKey = ''): TranslatorConfig => {
+    switch (kind) {
+        case 'deepl':
+            return {kind: 'deepl', api_key: apiKey, api_url: 'https://api-free.deepl.com'};
+        case 'libretranslate':
+            return {kind: 'libretranslate', url: 'http://localhost:5000', api_key: apiKey};
+        case 'openai':
+            return {kind: 'openai', url: 'https://api.openai.com/v1', api_key: apiKey, model: 'gpt-4o-mini'};
+        case 'offline':
+            return {kind: 'offline', model: 'mul-en'};
+        default:
+            return {kind: 'google', url: 'https://translate.googleapis.com'};
+    }
+};
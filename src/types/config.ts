@@ -8,9 +8,47 @@ export type Region = {
     h: number
 }
 
-export type Config = {
+export type WindowTarget = {
+    app_name: string,
+    title: string
+}
+
+export type TranslatorConfig =
+    | { kind: 'google', url: string }
+    | { kind: 'deepl', api_key: string, api_url: string }
+    | { kind: 'libretranslate', url: string, api_key?: string }
+    | { kind: 'openai', url: string, api_key?: string, model: string }
+    | { kind: 'offline', model: string }
+
+export type Hotkeys = {
+    pause_resume?: string,
+    reselect_region?: string,
+    toggle_overlay?: string,
+    retranslate?: string,
+    copy_translation?: string,
+    cycle_profile?: string
+}
+
+export type OcrModels = {
+    detection: string,
+    recognition: string
+}
+
+export type CaptureRegion = {
+    id: string,
+    name: string,
     region?: Region,
+    window?: WindowTarget,
     monitor: number,
+    interval_ms: number,
+    adaptive: boolean,
+    lang: string,
+    source_lang: string
+}
+
+export type Config = {
+    version: number,
+    regions: CaptureRegion[],
     text_color: string,
     text_align:
         | 'T:L'
@@ -25,6 +63,19 @@ export type Config = {
     text_size: number,
     background_color: string,
     blur_background: boolean,
-    interval: number,
-    lang: string
+    preserve_layout: boolean,
+    similarity_threshold: number,
+    frame_change_threshold: number,
+    translator: TranslatorConfig,
+    api_keys: Record<string, string>,
+    ocr_models: OcrModels,
+    model_mirror?: string | null,
+    hotkeys: Hotkeys
+}
+
+export type ProfilesList = {
+    active?: string,
+    auto_switch: boolean,
+    bindings: Record<string, string[]>,
+    profiles: string[],
 }
@@ -1,8 +1,26 @@
 /* Copyright © 2025 Xylobyte
  * SPDX-License-Identifier: AGPL-3.0-or-later */
 
-export interface DownloadProgress {
-    file: string,
+export type DownloadStatus = "queued" | "downloading" | "verifying" | "failed" | "cancelled" | "done";
+
+export interface DownloadState {
+    id: string,
+    url: string,
+    status: DownloadStatus,
     progress: number,
-    total_size: number,
-}
\ No newline at end of file
+    total_size?: number,
+    attempt: number,
+    error?: string,
+}
+export interface ModelStatus {
+    id: string,
+    name: string,
+    kind: "detection" | "recognition",
+    version: string,
+    url: string,
+    file: string,
+    size?: number,
+    sha256?: string,
+    scripts: string[],
+    installed: boolean,
+}
@@ -0,0 +1,7 @@
+/* Copyright © 2025 Xylobyte
+ * SPDX-License-Identifier: AGPL-3.0-or-later */
+
+export interface RuntimeError {
+    region: string,
+    message: string,
+}
@@ -5,6 +5,10 @@ export enum Events {
     OnOffConfigTrayItem = "OnOffConfigTrayItem",
     RefreshOverlay = "RefreshOverlay",
     NewTranslatedText = "NewTranslatedText",
-    DownloadProgress = "DownloadProgress",
-    StopDownload = "StopDownload",
+    DownloadStatus = "DownloadStatus",
+    ModelsReady = "ModelsReady",
+    RuntimeError = "RuntimeError",
+    ProfilesChanged = "ProfilesChanged",
+    RuntimeModeChanged = "RuntimeModeChanged",
+    RuntimeStats = "RuntimeStats",
 }
\ No newline at end of file
@@ -0,0 +1,44 @@
+/* Copyright © 2025 Xylobyte
+ * SPDX-License-Identifier: AGPL-3.0-or-later */
+
+export interface TextBlock {
+    text: string,
+    x: number,
+    y: number,
+    w: number,
+    h: number,
+}
+
+export interface TranslatedText {
+    region: string,
+    text: string,
+    blocks: TextBlock[],
+    source_lang?: string,
+    target_lang: string,
+}
+
+export type RuntimeMode = "running" | "paused" | "oneshot";
+
+export interface StageLatency {
+    capture_ms: number,
+    detect_ms: number,
+    recognize_ms: number,
+    translate_ms: number,
+    emit_ms: number,
+    total_ms: number,
+}
+
+export interface RegionStats {
+    region: string,
+    captures: number,
+    unchanged_frames: number,
+    translations: number,
+    cache_hits: number,
+    cache_misses: number,
+    cancelled: number,
+    request_errors: number,
+    errors: number,
+    recognized_line_ratio?: number,
+    last: StageLatency,
+    average: StageLatency,
+}